@@ -0,0 +1,319 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::io::{BufReader, Read};
+
+// write bits, least significant first
+struct BitWriter {
+    out: Vec<u8>,
+    bitbuf: u32,
+    bitcount: u8,
+}
+
+impl BitWriter {
+    fn new(lit: u8, dict: u8) -> Self {
+        let mut w = BitWriter {
+            out: vec![],
+            bitbuf: 0,
+            bitcount: 0,
+        };
+        w.bits(lit as u32, 8);
+        w.bits(dict as u32, 8);
+        w
+    }
+
+    fn bits(&mut self, value: u32, n: u8) {
+        self.bitbuf |= (value & ((1 << n) - 1)) << self.bitcount;
+        self.bitcount += n;
+        while self.bitcount >= 8 {
+            self.out.push(self.bitbuf as u8);
+            self.bitbuf >>= 8;
+            self.bitcount -= 8;
+        }
+    }
+
+    fn literal(&mut self, value: u8) {
+        self.bits(0, 1);
+        self.bits(value as u32, 8);
+    }
+
+    // (bits, length) for a few Huffman coded literals, ready to write
+    const CODED: &'static [(u8, u32, u8)] = &[
+        (b'e', 0x1b, 5),
+        (b't', 0x05, 5),
+        (b'a', 0x07, 5),
+        (b'o', 0x1d, 5),
+        (b'i', 0x0b, 5),
+        (b'n', 0x03, 5),
+        (b' ', 0x0f, 4),
+        (b's', 0x15, 5),
+        (b'h', 0x0a, 6),
+        (b'r', 0x0d, 5),
+        (b'd', 0x3a, 6),
+        (b'l', 0x13, 5),
+        (b'u', 0x19, 5),
+    ];
+
+    fn literal_coded(&mut self, i: usize) -> u8 {
+        let (value, code, len) = Self::CODED[i % Self::CODED.len()];
+        self.bits(0, 1);
+        self.bits(code, len);
+        value
+    }
+
+    // copy 264 to 518 bytes from up to 256 bytes back, dictionary size 6
+    fn long_copy(&mut self, len: usize, dist: usize) {
+        const DIST_CODES: &[(u32, u8)] =
+            &[(0x3, 2), (0xd, 4), (0x5, 4), (0x19, 5)];
+        assert!((264..=518).contains(&len) && (1..=256).contains(&dist));
+        self.bits(1, 1);
+        self.bits(0x0, 7);
+        self.bits((len - 264) as u32, 8);
+        let (code, n) = DIST_CODES[(dist - 1) >> 6];
+        self.bits(code, n);
+        self.bits((dist - 1) as u32, 6);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        // end code
+        self.bits(1, 1);
+        self.bits(0x0, 7);
+        self.bits(0xff, 8);
+        if self.bitcount > 0 {
+            self.out.push(self.bitbuf as u8);
+        }
+        self.out
+    }
+}
+
+// a simple xorshift, to avoid depending on rand
+fn random(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+const SIZE: usize = 1 << 20;
+
+// long matches, after a short run of literals
+fn compressible() -> (Vec<u8>, Vec<u8>) {
+    let mut rng = 1;
+    let mut w = BitWriter::new(0, 6);
+    let mut expected = vec![];
+    for i in 0..256 {
+        w.literal(i as u8);
+        expected.push(i as u8);
+    }
+    while expected.len() < SIZE {
+        let len = 264 + random(&mut rng) as usize % 255;
+        let dist = 1 + random(&mut rng) as usize % 256;
+        w.long_copy(len, dist);
+        for _ in 0..len {
+            expected.push(expected[expected.len() - dist]);
+        }
+    }
+    (w.finish(), expected)
+}
+
+// long matches from only a few bytes back, which overlap themselves
+fn runs() -> (Vec<u8>, Vec<u8>) {
+    let mut rng = 1;
+    let mut w = BitWriter::new(0, 6);
+    let mut expected = vec![];
+    for i in 0..4 {
+        w.literal(i as u8);
+        expected.push(i as u8);
+    }
+    while expected.len() < SIZE {
+        let len = 264 + random(&mut rng) as usize % 255;
+        let dist = 1 + random(&mut rng) as usize % 4;
+        w.long_copy(len, dist);
+        for _ in 0..len {
+            expected.push(expected[expected.len() - dist]);
+        }
+    }
+    (w.finish(), expected)
+}
+
+// uncoded literals only
+fn incompressible() -> (Vec<u8>, Vec<u8>) {
+    let mut rng = 1;
+    let mut w = BitWriter::new(0, 6);
+    let mut expected = vec![];
+    while expected.len() < SIZE {
+        let value = random(&mut rng) as u8;
+        w.literal(value);
+        expected.push(value);
+    }
+    (w.finish(), expected)
+}
+
+// Huffman coded literals only
+fn coded_literals() -> (Vec<u8>, Vec<u8>) {
+    let mut rng = 1;
+    let mut w = BitWriter::new(1, 6);
+    let mut expected = vec![];
+    while expected.len() < SIZE {
+        let value = w.literal_coded(random(&mut rng) as usize);
+        expected.push(value);
+    }
+    (w.finish(), expected)
+}
+
+// text-like data from a few words, compressed with implode, for a
+// realistic mix of coded literals and short copies
+fn mixed() -> (Vec<u8>, Vec<u8>) {
+    const WORDS: &[&str] = &[
+        "the ",
+        "of ",
+        "and ",
+        "to ",
+        "in ",
+        "is ",
+        "that ",
+        "for ",
+        "stream ",
+        "window ",
+        "literal ",
+        "copy ",
+        "distance ",
+        "code ",
+        "length ",
+        "huffman ",
+        "table ",
+        "header ",
+        "\n",
+    ];
+    let mut rng = 1;
+    let mut expected = vec![];
+    while expected.len() < SIZE {
+        let word = WORDS[random(&mut rng) as usize % WORDS.len()];
+        expected.extend_from_slice(word.as_bytes());
+    }
+    let encoded = explode::implode(
+        &expected,
+        explode::LiteralMode::Coded,
+        explode::DictSize::Size4K,
+    )
+    .unwrap();
+    (encoded, expected)
+}
+
+fn bench_explode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("explode");
+    group.sample_size(20);
+
+    let cases = [
+        ("compressible", compressible()),
+        ("runs", runs()),
+        ("incompressible", incompressible()),
+        ("coded_literals", coded_literals()),
+        ("mixed", mixed()),
+    ];
+    for (name, (encoded, expected)) in cases.iter() {
+        assert_eq!(&explode::explode(encoded).unwrap(), expected);
+        group.throughput(Throughput::Bytes(expected.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| explode::explode(encoded).unwrap())
+        });
+    }
+    group.finish();
+
+    // through a buffered ExplodeReader
+    let mut group = c.benchmark_group("reader");
+    group.sample_size(20);
+    for (name, (encoded, expected)) in cases.iter() {
+        group.throughput(Throughput::Bytes(expected.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let mut r = explode::ExplodeReader::buffered(&encoded[..]);
+                let mut out = Vec::with_capacity(expected.len());
+                r.read_to_end(&mut out).unwrap();
+                out
+            })
+        });
+    }
+    group.finish();
+
+    // through ExplodeReader, reading from an unbuffered inner
+    let mut group = c.benchmark_group("reader_unbuffered");
+    group.sample_size(20);
+    for (name, (encoded, expected)) in cases.iter() {
+        group.throughput(Throughput::Bytes(expected.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let mut r = explode::ExplodeReader::new(&encoded[..]);
+                let mut out = Vec::with_capacity(expected.len());
+                r.read_to_end(&mut out).unwrap();
+                out
+            })
+        });
+    }
+    group.finish();
+
+    // through ExplodeReader, straight from an already buffered inner
+    let mut group = c.benchmark_group("reader_bufread");
+    group.sample_size(20);
+    for (name, (encoded, expected)) in cases.iter() {
+        group.throughput(Throughput::Bytes(expected.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                let inner = BufReader::new(&encoded[..]);
+                let mut r = explode::ExplodeReader::from_bufread(inner);
+                let mut out = Vec::with_capacity(expected.len());
+                r.read_to_end(&mut out).unwrap();
+                out
+            })
+        });
+    }
+    group.finish();
+}
+
+// many small members, cut from mixed text and compressed separately
+fn members() -> Vec<Vec<u8>> {
+    let (_, expected) = mixed();
+    expected
+        .chunks(256)
+        .take(4096)
+        .map(|chunk| {
+            explode::implode(
+                chunk,
+                explode::LiteralMode::Coded,
+                explode::DictSize::Size4K,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+fn bench_pooled(c: &mut Criterion) {
+    let members = members();
+    let total: usize = members
+        .iter()
+        .map(|m| explode::explode(m).unwrap().len())
+        .sum();
+
+    // one Explode per call, or one per thread
+    let mut group = c.benchmark_group("members");
+    group.sample_size(20);
+    group.throughput(Throughput::Bytes(total as u64));
+    group.bench_function("explode", |b| {
+        b.iter(|| {
+            members
+                .iter()
+                .map(|m| explode::explode(m).unwrap().len())
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("explode_pooled", |b| {
+        b.iter(|| {
+            members
+                .iter()
+                .map(|m| explode::explode_pooled(m).unwrap().len())
+                .sum::<usize>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_explode, bench_pooled);
+criterion_main!(benches);
@@ -0,0 +1,161 @@
+// decompress files from the command line, or check that this crate
+// works in the current environment
+//
+// usage:
+//   explode INPUT OUTPUT
+//   explode selftest
+
+use std::io::{Read, Write};
+
+// known streams and their decompressed contents
+const VECTORS: &[(&str, &[u8], &[u8])] = &[
+    (
+        "newsgroup",
+        &[0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f],
+        b"AIAIAIAIAIAIA",
+    ),
+    ("empty", &[0x00, 0x04, 0x01, 0xff], b""),
+    (
+        "undhr",
+        include_bytes!("../src/examples/undhr.z"),
+        include_bytes!("../src/examples/undhr.md"),
+    ),
+];
+
+// generated inputs, compressed here for round trips
+fn generated() -> Vec<(&'static str, Vec<u8>)> {
+    const SIZE: usize = 100_000;
+
+    // a simple xorshift, to avoid depending on rand
+    let mut state: u32 = 1;
+    let random = (0..SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+
+    let words = ["implode ", "explode ", "window ", "copy ", "literal\n"];
+    let text = (0..)
+        .flat_map(|i: usize| words[i * 7 % words.len()].bytes())
+        .take(SIZE)
+        .collect();
+
+    vec![("random", random), ("zeros", vec![0; SIZE]), ("text", text)]
+}
+
+// compress with ImplodeWriter, then decompress with ExplodeWriter,
+// a few bytes at a time
+fn writers(
+    data: &[u8],
+    lit: explode::LiteralMode,
+    dict: explode::DictSize,
+) -> std::io::Result<Vec<u8>> {
+    let mut w = explode::ImplodeWriter::new(vec![], lit, dict);
+    for chunk in data.chunks(1000) {
+        w.write_all(chunk)?;
+    }
+    let encoded = w.finish()?;
+
+    let mut w = explode::ExplodeWriter::new(vec![]);
+    for chunk in encoded.chunks(7) {
+        w.write_all(chunk)?;
+    }
+    w.finish()
+}
+
+fn check(
+    name: &str,
+    method: &str,
+    result: Result<Vec<u8>, Box<dyn std::error::Error>>,
+    expected: &[u8],
+) -> bool {
+    match result {
+        Ok(ref ours) if ours == expected => {
+            println!("ok      {} ({})", name, method);
+            true
+        }
+        Ok(_) => {
+            println!("FAILED  {} ({}): output mismatch", name, method);
+            false
+        }
+        Err(e) => {
+            println!("FAILED  {} ({}): {}", name, method, e);
+            false
+        }
+    }
+}
+
+fn selftest() -> bool {
+    let mut ok = true;
+    for (name, encoded, decoded) in VECTORS {
+        let result = explode::explode(encoded).map_err(|e| e.into());
+        ok &= check(name, "explode", result, decoded);
+
+        let mut reader = explode::ExplodeReader::new(*encoded);
+        let mut ours = vec![];
+        let result = reader.read_to_end(&mut ours).map(|_| ours);
+        let result = result.map_err(|e| e.into());
+        ok &= check(name, "reader", result, decoded);
+    }
+
+    // and round trips through implode, with every header, and
+    // through the writers
+    let lits = [explode::LiteralMode::Uncoded, explode::LiteralMode::Coded];
+    let dicts = [
+        explode::DictSize::Size1K,
+        explode::DictSize::Size2K,
+        explode::DictSize::Size4K,
+    ];
+    let mut inputs: Vec<(&str, Vec<u8>)> = VECTORS
+        .iter()
+        .map(|(name, _, decoded)| (*name, decoded.to_vec()))
+        .collect();
+    inputs.extend(generated());
+    for (name, decoded) in inputs.iter() {
+        for lit in lits.iter() {
+            for dict in dicts.iter() {
+                let result = explode::implode(decoded, *lit, *dict)
+                    .and_then(|encoded| explode::explode(&encoded))
+                    .map_err(|e| e.into());
+                ok &= check(name, "round trip", result, decoded);
+
+                let result = writers(decoded, *lit, *dict);
+                let result = result.map_err(|e| e.into());
+                ok &= check(name, "writers", result, decoded);
+            }
+        }
+    }
+    ok
+}
+
+fn decompress(input: &str, output: &str) -> explode::Result<()> {
+    let data = std::fs::read(input)?;
+    let result = explode::explode(&data)?;
+    std::fs::write(output, result)?;
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let ok = match args.as_slice() {
+        [cmd] if cmd == "selftest" => selftest(),
+        [input, output] => match decompress(input, output) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                false
+            }
+        },
+        _ => {
+            eprintln!("usage: explode INPUT OUTPUT");
+            eprintln!("       explode selftest");
+            false
+        }
+    };
+    if !ok {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // any input at all must return, and never panic
+    if let Ok((_, used)) = explode::explode_counted(data) {
+        // a successful stream cut short is always incomplete
+        if used > 0 {
+            assert_eq!(
+                explode::explode(&data[..used - 1]),
+                Err(explode::Error::IncompleteInput)
+            );
+        }
+    }
+});
@@ -0,0 +1,327 @@
+use crate::explode::as_uninit;
+use crate::reader::{poll_read, ReadState, Source};
+use crate::Explode;
+
+use std::io::Result;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// how much input to read from inner at once
+const INPUT_SIZE: usize = 8192;
+
+/// An asynchronous reader that decompresses.
+///
+/// This is the asynchronous version of
+/// [`ExplodeReader`](struct.ExplodeReader.html). It implements
+/// tokio's [`AsyncRead`][tokio] with the `tokio` feature, and the
+/// [`AsyncRead`][futures] from futures, used by async-std and smol,
+/// with the `futures` feature. Input is read from `inner` in large
+/// blocks, and any input read past the end of the compressed stream
+/// is available from [`buffer`](#method.buffer). To read several
+/// streams stored back to back as one, use
+/// [`multi_member`](#method.multi_member).
+///
+///  [tokio]: https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html
+///  [futures]: https://docs.rs/futures/0.3/futures/io/trait.AsyncRead.html
+///
+/// This is only available with the `tokio` or `futures` features.
+///
+/// ```
+/// # #[cfg(feature = "tokio")]
+/// # fn main() -> std::io::Result<()> {
+/// use tokio::io::AsyncReadExt;
+///
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let mut reader = explode::AsyncExplodeReader::new(&bytes[..]);
+/// let mut decompressed = vec![];
+/// futures::executor::block_on(reader.read_to_end(&mut decompressed))?;
+/// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// # #[cfg(not(feature = "tokio"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct AsyncExplodeReader<R> {
+    inner: Pin<Box<R>>,
+    state: ReadState,
+    input: InputBuffer,
+}
+
+// input read from inner, and not yet used
+#[derive(Debug)]
+struct InputBuffer {
+    data: Box<[u8]>,
+    pos: usize,
+    end: usize,
+    eof: bool,
+}
+
+// the input of an AsyncExplodeReader, as a Source. fill reads from
+// inner into the buffer
+struct AsyncSource<'a, R, F> {
+    inner: Pin<&'a mut R>,
+    input: &'a mut InputBuffer,
+    fill: F,
+}
+
+impl<'a, R, F> Source for AsyncSource<'a, R, F>
+where
+    F: FnMut(Pin<&mut R>, &mut [u8]) -> Poll<Result<usize>>,
+{
+    fn poll_fill(&mut self) -> Poll<Result<&[u8]>> {
+        let input = &mut *self.input;
+        if input.pos == input.end && !input.eof {
+            let n = match (self.fill)(self.inner.as_mut(), &mut input.data) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Ready(Ok(n)) => n,
+            };
+            input.pos = 0;
+            input.end = n;
+            input.eof = n == 0;
+        }
+        Poll::Ready(Ok(&input.data[input.pos..input.end]))
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.input.pos += amt;
+    }
+}
+
+impl<R> AsyncExplodeReader<R> {
+    /// Create a new decompression wrapper around `inner`.
+    pub fn new(inner: R) -> Self {
+        Self::with_explode(inner, Explode::new())
+    }
+
+    /// Create a new decompression wrapper around `inner`, continuing
+    /// from an existing decompression state `dec`.
+    ///
+    /// As with
+    /// [`ExplodeReader::with_explode`](struct.ExplodeReader.html#method.with_explode),
+    /// `dec` must be ready for the next byte from `inner`.
+    pub fn with_explode(inner: R, dec: Explode) -> Self {
+        AsyncExplodeReader {
+            inner: Box::pin(inner),
+            state: ReadState::new(dec),
+            input: InputBuffer {
+                data: vec![0; INPUT_SIZE].into_boxed_slice(),
+                pos: 0,
+                end: 0,
+                eof: false,
+            },
+        }
+    }
+
+    /// Read several compressed streams stored back to back, as if
+    /// their output was one stream.
+    ///
+    /// This works as in
+    /// [`ExplodeReader::multi_member`](struct.ExplodeReader.html#method.multi_member).
+    /// The number of streams finished so far is
+    /// [`member_count`](#method.member_count).
+    pub fn multi_member(mut self, multi: bool) -> Self {
+        self.state.multi_member = multi;
+        self
+    }
+
+    /// Returns the number of compressed streams finished so far.
+    ///
+    /// This is at most 1, unless
+    /// [`multi_member`](#method.multi_member) is used.
+    pub fn member_count(&self) -> u64 {
+        self.state.members
+    }
+
+    /// Get the input that has been read from `inner`, but not yet
+    /// decompressed.
+    pub fn buffer(&self) -> &[u8] {
+        &self.input.data[self.input.pos..self.input.end]
+    }
+
+    // decompress into out, reading from inner with fill when the
+    // input buffer is empty
+    fn poll_explode<F>(
+        &mut self,
+        out: &mut [u8],
+        fill: F,
+    ) -> Poll<Result<usize>>
+    where
+        F: FnMut(Pin<&mut R>, &mut [u8]) -> Poll<Result<usize>>,
+    {
+        let mut src = AsyncSource {
+            inner: self.inner.as_mut(),
+            input: &mut self.input,
+            fill,
+        };
+        poll_read(&mut self.state, &mut src, as_uninit(out))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> tokio::io::AsyncRead for AsyncExplodeReader<R>
+where
+    R: tokio::io::AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        let out = buf.initialize_unfilled();
+        let len = match this.poll_explode(out, |inner, input| {
+            let mut input = tokio::io::ReadBuf::new(input);
+            inner
+                .poll_read(cx, &mut input)
+                .map_ok(|()| input.filled().len())
+        }) {
+            Poll::Pending => return Poll::Pending,
+            Poll::Ready(result) => result?,
+        };
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<R> futures_io::AsyncRead for AsyncExplodeReader<R>
+where
+    R: futures_io::AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.get_mut()
+            .poll_explode(buf, |inner, input| inner.poll_read(cx, input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncExplodeReader;
+    use crate::examples::EXAMPLES;
+    use futures::executor;
+    use std::io::ErrorKind;
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_reader() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        for (encoded, decoded) in EXAMPLES {
+            // a tiny pipe, so reads are often pending mid-token
+            let (mut tx, rx) = tokio::io::duplex(7);
+            let mut reader = AsyncExplodeReader::new(rx);
+            let mut ours = vec![];
+            let write = async {
+                tx.write_all(encoded).await.unwrap();
+                drop(tx);
+            };
+            let read = reader.read_to_end(&mut ours);
+            let (_, result) =
+                executor::block_on(futures::future::join(write, read));
+            result.unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn async_reader_errors() {
+        use tokio::io::AsyncReadExt;
+        let (encoded, _) = EXAMPLES[2];
+
+        // truncated
+        let mut reader = AsyncExplodeReader::new(&encoded[..100]);
+        let mut ours = vec![];
+        let err = executor::block_on(reader.read_to_end(&mut ours));
+        assert_eq!(err.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+        // bad data
+        let mut reader = AsyncExplodeReader::new(&[0x00, 0x07][..]);
+        let err = executor::block_on(reader.read_to_end(&mut ours));
+        assert_eq!(err.unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // extra input is left in the buffer
+        let mut data = encoded.to_vec();
+        data.push(42);
+        let mut reader = AsyncExplodeReader::new(&data[..]);
+        let mut ours = vec![];
+        executor::block_on(reader.read_to_end(&mut ours)).unwrap();
+        assert_eq!(reader.buffer(), &[42]);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn async_reader_futures() {
+        use futures::io::{AsyncReadExt, Cursor};
+        use futures::TryStreamExt;
+
+        for (encoded, decoded) in EXAMPLES {
+            let mut reader = AsyncExplodeReader::new(Cursor::new(encoded));
+            let mut ours = vec![];
+            executor::block_on(reader.read_to_end(&mut ours)).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            // small reads, from input that arrives one byte at a time
+            let chunks = encoded.chunks(1).map(Ok::<_, std::io::Error>);
+            let input = futures::stream::iter(chunks).into_async_read();
+            let mut reader = AsyncExplodeReader::new(input);
+            let mut ours = vec![];
+            let mut buf = [0; 7];
+            executor::block_on(async {
+                loop {
+                    match reader.read(&mut buf).await.unwrap() {
+                        0 => break,
+                        n => ours.extend_from_slice(&buf[..n]),
+                    }
+                }
+            });
+            assert_eq!(*decoded, &ours[..]);
+        }
+
+        let (encoded, _) = EXAMPLES[2];
+        let mut reader =
+            AsyncExplodeReader::new(Cursor::new(&encoded[..100]));
+        let err = executor::block_on(reader.read_to_end(&mut vec![]));
+        assert_eq!(err.unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn async_reader_multi_member() {
+        use futures::io::{AsyncRead, AsyncReadExt, Cursor};
+        use std::pin::Pin;
+        use std::task::{Context, Poll};
+
+        // interrupts before every read
+        struct Interrupting<R>(R, bool);
+        impl<R: AsyncRead + Unpin> AsyncRead for Interrupting<R> {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<std::io::Result<usize>> {
+                self.1 = !self.1;
+                if self.1 {
+                    return Poll::Ready(Err(ErrorKind::Interrupted.into()));
+                }
+                Pin::new(&mut self.0).poll_read(cx, buf)
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let data = [*encoded, *encoded].concat();
+            let inner = Interrupting(Cursor::new(data), false);
+            let mut reader =
+                AsyncExplodeReader::new(inner).multi_member(true);
+            let mut ours = vec![];
+            executor::block_on(reader.read_to_end(&mut ours)).unwrap();
+            assert_eq!([*decoded, *decoded].concat(), ours);
+            assert_eq!(reader.member_count(), 2);
+        }
+    }
+}
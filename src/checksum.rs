@@ -0,0 +1,107 @@
+/// A checksum computed over decompressed output.
+///
+/// Implement this to compute CRC-32, Adler-32, or any other checksum
+/// while decompressing, with
+/// [`ExplodeReader::with_checksum`](struct.ExplodeReader.html#method.with_checksum)
+/// or
+/// [`Explode::copy_to_with_checksum`](struct.Explode.html#method.copy_to_with_checksum).
+///
+/// ```
+/// # #[cfg(feature = "std")]
+/// # fn main() -> std::io::Result<()> {
+/// # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// # let some_file = std::io::Cursor::new(&bytes);
+/// use explode::Checksum;
+/// use std::io::Read;
+///
+/// // a sum of all bytes, as an example
+/// #[derive(Debug, Default)]
+/// struct Sum(u64);
+///
+/// impl Checksum for Sum {
+///     fn update(&mut self, data: &[u8]) {
+///         self.0 += data.iter().map(|b| *b as u64).sum::<u64>();
+///     }
+///
+///     fn finalize(self) -> u64 {
+///         self.0
+///     }
+/// }
+///
+/// let reader = explode::ExplodeReader::new(some_file);
+/// let mut reader = reader.with_checksum(Sum::default());
+/// let mut decompressed = vec![];
+/// reader.read_to_end(&mut decompressed)?;
+/// assert_eq!(reader.into_checksum().finalize(), 6 * 0x49 + 7 * 0x41);
+/// # Ok(()) }
+/// # #[cfg(not(feature = "std"))]
+/// # fn main() {}
+/// ```
+pub trait Checksum {
+    /// Add `data` to the checksum.
+    fn update(&mut self, data: &[u8]);
+
+    /// Return the final checksum value.
+    fn finalize(self) -> u64;
+}
+
+/// A [`Checksum`](trait.Checksum.html) that does nothing, and always
+/// returns 0.
+///
+/// This is the default for
+/// [`ExplodeReader`](struct.ExplodeReader.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoChecksum;
+
+impl Checksum for NoChecksum {
+    fn update(&mut self, _data: &[u8]) {}
+
+    fn finalize(self) -> u64 {
+        0
+    }
+}
+
+/// A [`Checksum`](trait.Checksum.html) that computes CRC-32.
+///
+/// This is the CRC-32 used by ZIP archives, and also by gzip and PNG,
+/// with the IEEE polynomial `0x04c11db7`. It is the checksum stored
+/// for each member of a ZIP archive. Use it with
+/// [`ExplodeReader::with_crc32`](struct.ExplodeReader.html#method.with_crc32),
+/// or with anything else that takes a `Checksum`.
+///
+/// This is only available with the `crc` feature.
+///
+/// ```
+/// use explode::{Checksum, Crc32};
+///
+/// let mut crc = Crc32::new();
+/// crc.update(b"123456789");
+/// assert_eq!(crc.value(), 0xcbf43926);
+/// ```
+#[cfg(feature = "crc")]
+#[derive(Clone, Debug, Default)]
+pub struct Crc32(crc32fast::Hasher);
+
+#[cfg(feature = "crc")]
+impl Crc32 {
+    /// Create a new CRC-32 of no data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the CRC-32 of the data so far.
+    pub fn value(&self) -> u32 {
+        self.0.clone().finalize()
+    }
+}
+
+#[cfg(feature = "crc")]
+impl Checksum for Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> u64 {
+        self.0.finalize() as u64
+    }
+}
@@ -1,15 +1,47 @@
-// canonical Huffman codes
-// T can be either &[u8] or Vec<u8>
+use alloc::{vec, vec::Vec};
+use core::convert::TryFrom;
+
+/// A canonical Huffman code.
+///
+/// This stores the number of codes of each length in `T`, which is
+/// either `&[u8]` or `Vec<u8>`. The symbols, in order of their codes,
+/// are stored in `U`, which is a slice or `Vec` of any symbol type.
+///
+/// Codes are canonical, most significant bit first. Implode streams
+/// store every bit of a code inverted, and this is left to the caller.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct CanonicalHuffman<T> {
+pub struct CanonicalHuffman<T, U = T> {
     counts: T,
-    symbols: T,
+    symbols: U,
+}
+
+// a slice of symbols, of any type
+pub trait Symbols {
+    type Symbol: Copy + PartialEq;
+
+    fn symbols(&self) -> &[Self::Symbol];
+}
+
+impl<S: Copy + PartialEq> Symbols for &[S] {
+    type Symbol = S;
+
+    fn symbols(&self) -> &[S] {
+        self
+    }
+}
+
+impl<S: Copy + PartialEq> Symbols for Vec<S> {
+    type Symbol = S;
+
+    fn symbols(&self) -> &[S] {
+        self
+    }
 }
 
 // decode state
 #[derive(Clone, Debug)]
-pub struct Decoder<'a, T> {
-    codebook: &'a CanonicalHuffman<T>,
+pub struct Decoder<'a, T, U = T> {
+    codebook: &'a CanonicalHuffman<T, U>,
     code: u32,    // code so far
     bits: usize,  // how many bits in the code
     index: usize, // index of first code of this length in symbol table
@@ -18,10 +50,10 @@ pub struct Decoder<'a, T> {
 
 // decode result
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub enum DecodeResult {
+pub enum DecodeResult<S = u8> {
     Incomplete,
     Invalid,
-    Ok(u8),
+    Ok(S),
 }
 
 #[cfg(test)]
@@ -45,13 +77,32 @@ impl CanonicalHuffman<Vec<u8>> {
         Self::new_from_lengths(&lengths[..symbol])
     }
 
-    // create from a list of symbol lengths
-    // returns None if oversubscribed
+    // turn a Vec-based table into a slice-based one
+    // used mostly for comparison
+    pub fn as_ref(&self) -> CanonicalHuffman<&[u8]> {
+        CanonicalHuffman {
+            counts: &self.counts,
+            symbols: &self.symbols,
+        }
+    }
+}
+
+impl<S> CanonicalHuffman<Vec<u8>, Vec<S>>
+where
+    S: Copy + PartialEq + TryFrom<usize>,
+{
+    /// Create a code from the code length of each symbol.
+    ///
+    /// Symbol `i` has code length `lengths[i]`, and symbols with
+    /// length 0 have no code. Returns `None` if there are too many
+    /// short codes to all be distinct, if more than 255 symbols have
+    /// the same length, or if some symbol does not fit in `S`.
     pub fn new_from_lengths(lengths: &[u8]) -> Option<Self> {
         let max_len = (*lengths.iter().max().unwrap_or(&0) + 1) as usize;
-        let mut counts = vec![0; max_len];
+        let mut counts: Vec<u8> = vec![0; max_len];
         for len in lengths.iter() {
-            counts[*len as usize] += 1;
+            let count = &mut counts[*len as usize];
+            *count = count.checked_add(1)?;
         }
 
         if counts[0] as usize == lengths.len() {
@@ -64,17 +115,18 @@ impl CanonicalHuffman<Vec<u8>> {
 
         // check for oversubscription
         // one code of length zero
-        let mut symbols_left = 1;
-        for len in 1..max_len {
+        let mut symbols_left: usize = 1;
+        for count in counts.iter().skip(1) {
             // one more bit doubles number of symbols left
-            symbols_left <<= 1;
+            // (this can be far more than a u8 holds, for long codes)
+            symbols_left = symbols_left.saturating_mul(2);
             // do we have enough left for this size?
-            if symbols_left < counts[len] {
+            if symbols_left < *count as usize {
                 // over-subscribed
                 return None;
             }
             // remove the symbols used here
-            symbols_left -= counts[len];
+            symbols_left -= *count as usize;
         }
 
         // helper to build symbol table
@@ -88,37 +140,42 @@ impl CanonicalHuffman<Vec<u8>> {
         let mut symbols = vec![0; lengths.len()];
         for symbol in 0..lengths.len() {
             if lengths[symbol] > 0 {
-                symbols[offsets[lengths[symbol] as usize]] = symbol as u8;
+                symbols[offsets[lengths[symbol] as usize]] = symbol;
                 offsets[lengths[symbol] as usize] += 1;
             }
         }
+        let symbols = symbols
+            .into_iter()
+            .map(|symbol| S::try_from(symbol).ok())
+            .collect::<Option<_>>()?;
 
         Some(CanonicalHuffman { counts, symbols })
     }
-
-    // turn a Vec-based table into a slice-based one
-    // used mostly for comparison
-    pub fn as_ref(&self) -> CanonicalHuffman<&[u8]> {
-        CanonicalHuffman {
-            counts: &self.counts,
-            symbols: &self.symbols,
-        }
-    }
 }
 
 impl<'a> CanonicalHuffman<&'a [u8]> {
-    // create a code from an array of code counts per length, and symbols
-    // unsafe -- does not check that counts.iter().sum() == symbols.len()
+    /// Create a code from the number of codes of each length, and
+    /// the symbols in order of their codes.
+    ///
+    /// `counts[0]` is the number of symbols without a code, and is
+    /// otherwise unused.
+    ///
+    /// # Safety
+    ///
+    /// This does not check that `counts` and `symbols` agree. If
+    /// they do not, decoding and encoding may panic or give wrong
+    /// results, though they will never access memory out of bounds.
     pub const unsafe fn new(counts: &'a [u8], symbols: &'a [u8]) -> Self {
         CanonicalHuffman { counts, symbols }
     }
 }
 
-impl<T> CanonicalHuffman<T>
+impl<T, U> CanonicalHuffman<T, U>
 where
-    T: std::convert::AsRef<[u8]>,
+    T: AsRef<[u8]>,
+    U: Symbols,
 {
-    pub fn decoder(&self) -> Decoder<T> {
+    pub(crate) fn decoder(&self) -> Decoder<'_, T, U> {
         Decoder {
             codebook: self,
             code: 0,
@@ -127,13 +184,73 @@ where
             first: 0,
         }
     }
+
+    /// Check whether `other` is the same code, whatever storage
+    /// either one uses.
+    ///
+    /// Two codes are the same if they have the same number of codes of
+    /// each length, for the same symbols in the same order. This is
+    /// useful to check a custom table against one of the built in
+    /// tables, which are stored in slices.
+    ///
+    /// ```
+    /// use explode::tables::{CanonicalHuffman, LENGTH};
+    ///
+    /// let lengths: Vec<u8> = (0..16)
+    ///     .map(|s| LENGTH.encode(s).unwrap().1 as u8)
+    ///     .collect();
+    /// let ours: CanonicalHuffman<Vec<u8>, Vec<u8>> =
+    ///     CanonicalHuffman::new_from_lengths(&lengths).unwrap();
+    /// assert!(ours.structurally_eq(&LENGTH));
+    /// ```
+    pub fn structurally_eq<V, W>(
+        &self,
+        other: &CanonicalHuffman<V, W>,
+    ) -> bool
+    where
+        V: AsRef<[u8]>,
+        W: Symbols<Symbol = U::Symbol>,
+    {
+        self.counts.as_ref() == other.counts.as_ref()
+            && self.symbols.symbols() == other.symbols.symbols()
+    }
+
+    /// Find the code and code length for `symbol`, or `None` if it has
+    /// no code. This is the inverse of decoding, and codes are
+    /// canonical here, so invert them to write implode streams.
+    pub fn encode(&self, symbol: U::Symbol) -> Option<(u32, usize)> {
+        let counts = self.counts.as_ref();
+        let symbols = self.symbols.symbols();
+        let mut code = 0;
+        let mut index = 0;
+        for (len, count) in counts.iter().enumerate().skip(1) {
+            let count = *count as usize;
+            for i in 0..count {
+                if symbols[index + i] == symbol {
+                    return Some((code + i as u32, len));
+                }
+            }
+            index += count;
+            code = (code + count as u32) << 1;
+        }
+        None
+    }
 }
 
-impl<'a, T> Decoder<'a, T>
+impl<'a, T, U> Decoder<'a, T, U>
 where
-    T: std::convert::AsRef<[u8]>,
+    T: AsRef<[u8]>,
+    U: Symbols,
 {
-    pub fn feed(&mut self, bit: bool) -> DecodeResult {
+    // start over, to decode a new symbol
+    pub fn reset(&mut self) {
+        self.code = 0;
+        self.bits = 0;
+        self.index = 0;
+        self.first = 0;
+    }
+
+    pub fn feed(&mut self, bit: bool) -> DecodeResult<U::Symbol> {
         self.code |= bit as u32;
         self.bits += 1;
 
@@ -146,7 +263,7 @@ where
         if self.code < self.first + count {
             // this is a valid symbol
             let i = self.index + (self.code - self.first) as usize;
-            DecodeResult::Ok(self.codebook.symbols.as_ref()[i])
+            DecodeResult::Ok(self.codebook.symbols.symbols()[i])
         } else {
             // this is an incomplete or eventually invalid symbol
             self.index += count as usize;
@@ -156,20 +273,112 @@ where
             DecodeResult::Incomplete
         }
     }
+
+    // feed up to `available` bits from `bits`, least significant first
+    // returns the result, and how many bits were used
+    pub fn decode_prefix(
+        &mut self,
+        bits: u32,
+        available: usize,
+    ) -> (DecodeResult<U::Symbol>, usize) {
+        for i in 0..available {
+            match self.feed((bits >> i) & 1 == 1) {
+                DecodeResult::Incomplete => continue,
+                result => return (result, i + 1),
+            }
+        }
+        (DecodeResult::Incomplete, available)
+    }
+
+    // whether no bits have been fed since the last reset
+    #[inline]
+    pub(crate) fn is_reset(&self) -> bool {
+        self.bits == 0
+    }
+}
+
+// how many bits a FastTable looks at
+pub(crate) const FAST_BITS: usize = 8;
+
+// a lookup table from the next FAST_BITS bits of an implode stream,
+// which are inverted and least significant first, to a symbol and its
+// code length. codes longer than FAST_BITS are not in the table
+#[derive(Debug)]
+pub(crate) struct FastTable {
+    // code length in the high byte, symbol in the low, or 0 if none
+    entries: [u16; 1 << FAST_BITS],
+}
+
+impl FastTable {
+    // build the table, at compile time, from the arguments to
+    // CanonicalHuffman::new
+    pub(crate) const fn new(counts: &[u8], symbols: &[u8]) -> Self {
+        let mut entries = [0; 1 << FAST_BITS];
+        let mut code = 0;
+        let mut index = 0;
+        let mut len = 1;
+        while len < counts.len() && len <= FAST_BITS {
+            let mut i = 0;
+            while i < counts[len] as usize {
+                // reverse and invert the code, as in the stream
+                let mut stream = 0;
+                let mut k = 0;
+                while k < len {
+                    if (code + i) >> (len - 1 - k) & 1 == 0 {
+                        stream |= 1 << k;
+                    }
+                    k += 1;
+                }
+
+                // fill every entry that starts with this code
+                let entry = (len as u16) << 8 | symbols[index + i] as u16;
+                let mut high = 0;
+                while high < 1 << (FAST_BITS - len) {
+                    entries[stream | high << len] = entry;
+                    high += 1;
+                }
+                i += 1;
+            }
+            index += counts[len] as usize;
+            code = (code + counts[len] as usize) << 1;
+            len += 1;
+        }
+        FastTable { entries }
+    }
+
+    // look up a symbol from the low `available` bits of `bits`, and
+    // return it with how many bits it used
+    #[inline]
+    pub(crate) fn lookup(
+        &self,
+        bits: u32,
+        available: usize,
+    ) -> Option<(u8, usize)> {
+        let entry = self.entries[bits as usize & ((1 << FAST_BITS) - 1)];
+        let len = (entry >> 8) as usize;
+        if len == 0 || len > available {
+            None
+        } else {
+            Some((entry as u8, len))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::CanonicalHuffman;
     use super::DecodeResult;
+    use super::Symbols;
+    use alloc::{vec, vec::Vec};
 
     // helper function to decode an iterator
-    fn decodeiter<'a, T, I>(
-        table: &CanonicalHuffman<T>,
+    fn decodeiter<'a, T, U, I>(
+        table: &CanonicalHuffman<T, U>,
         bits: I,
-    ) -> Option<u8>
+    ) -> Option<U::Symbol>
     where
-        T: std::convert::AsRef<[u8]>,
+        T: AsRef<[u8]>,
+        U: Symbols,
         I: IntoIterator<Item = &'a bool>,
     {
         let mut d = table.decoder();
@@ -191,7 +400,8 @@ mod tests {
         // D = 111
         let a =
             CanonicalHuffman::new_from_packed_lengths(&[2, 1, 19]).unwrap();
-        let b = CanonicalHuffman::new_from_lengths(&[2, 1, 3, 3]).unwrap();
+        let b = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[2, 1, 3, 3])
+            .unwrap();
         let c =
             unsafe { CanonicalHuffman::new(&[0, 1, 1, 2], &[1, 0, 2, 3]) };
         assert_eq!(a, b);
@@ -199,13 +409,32 @@ mod tests {
         assert_eq!(b.as_ref(), c);
     }
 
+    #[test]
+    fn structurally_eq() {
+        let a = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[2, 1, 3, 3])
+            .unwrap();
+        let b =
+            unsafe { CanonicalHuffman::new(&[0, 1, 1, 2], &[1, 0, 2, 3]) };
+        assert!(a.structurally_eq(&b));
+        assert!(b.structurally_eq(&a));
+
+        // same counts, different symbols
+        let c =
+            unsafe { CanonicalHuffman::new(&[0, 1, 1, 2], &[0, 1, 2, 3]) };
+        assert!(!a.structurally_eq(&c));
+        // same symbols, longer max length
+        let d = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[2, 1, 4, 4])
+            .unwrap();
+        assert!(!a.structurally_eq(&d));
+    }
+
     #[test]
     fn oversubscribed() {
         // A = 0
         // B = 10
         // C = 11
         // D = ???
-        let a = CanonicalHuffman::new_from_lengths(&[1, 2, 2, 3]);
+        let a = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[1, 2, 2, 3]);
         assert_eq!(a, None);
     }
 
@@ -215,7 +444,8 @@ mod tests {
         // B = 0
         // C = 110
         // D = 111
-        let a = CanonicalHuffman::new_from_lengths(&[2, 1, 3, 3]).unwrap();
+        let a = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[2, 1, 3, 3])
+            .unwrap();
         assert_eq!(decodeiter(&a, &[true, false]), Some(0));
         assert_eq!(decodeiter(&a, &[false]), Some(1));
         assert_eq!(decodeiter(&a, &[true, true, false]), Some(2));
@@ -226,7 +456,8 @@ mod tests {
     fn undersubscribed() {
         // A = 0
         // B = 100
-        let a = CanonicalHuffman::new_from_lengths(&[1, 3]).unwrap();
+        let a =
+            CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[1, 3]).unwrap();
         assert_eq!(decodeiter(&a, &[false]), Some(0));
         assert_eq!(decodeiter(&a, &[true, false, false]), Some(1));
         assert_eq!(decodeiter(&a, &[true, true, true]), None);
@@ -249,7 +480,8 @@ mod tests {
     fn incomplete() {
         // B = 0
         // C = 1
-        let a = CanonicalHuffman::new_from_lengths(&[0, 1, 1]).unwrap();
+        let a = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[0, 1, 1])
+            .unwrap();
 
         assert_eq!(decodeiter(&a, &[false]), Some(1));
         assert_eq!(decodeiter(&a, &[true]), Some(2));
@@ -258,15 +490,124 @@ mod tests {
     #[test]
     fn empty() {
         // all codes are invalid in an empty table
-        let a = CanonicalHuffman::new_from_lengths(&[]).unwrap();
+        let a = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[]).unwrap();
 
         assert_eq!(a.decoder().feed(false), DecodeResult::Invalid);
         assert_eq!(a.decoder().feed(true), DecodeResult::Invalid);
 
         // if all symbols have 0 length, table is empty
-        let a = CanonicalHuffman::new_from_lengths(&[0, 0]).unwrap();
+        let a =
+            CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[0, 0]).unwrap();
 
         assert_eq!(a.decoder().feed(false), DecodeResult::Invalid);
         assert_eq!(a.decoder().feed(true), DecodeResult::Invalid);
     }
+
+    #[test]
+    fn encode() {
+        use crate::tables::{DISTANCE, LENGTH, LITERAL};
+
+        // every symbol in each table has a code, and nothing else does
+        let tables = [(&LITERAL, 256), (&LENGTH, 16), (&DISTANCE, 64)];
+        for (table, size) in tables.iter() {
+            for symbol in 0..=255u8 {
+                let encoded = table.encode(symbol);
+                assert_eq!(encoded.is_some(), (symbol as usize) < *size);
+                let (code, len) = match encoded {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let bits: Vec<bool> =
+                    (0..len).rev().map(|i| (code >> i) & 1 == 1).collect();
+                assert_eq!(decodeiter(table, &bits), Some(symbol));
+            }
+        }
+
+        let a = CanonicalHuffman::<Vec<u8>>::new_from_lengths(&[2, 1, 3, 0])
+            .unwrap();
+        assert_eq!(a.encode(0), Some((0b10, 2)));
+        assert_eq!(a.encode(1), Some((0b0, 1)));
+        assert_eq!(a.encode(2), Some((0b110, 3)));
+        assert_eq!(a.encode(3), None);
+    }
+
+    #[test]
+    fn wide_symbols() {
+        // more symbols than fit in a u8
+        let mut lengths = vec![2, 2];
+        lengths.extend_from_slice(&[9; 200]);
+        lengths.extend_from_slice(&[10; 100]);
+        let a: CanonicalHuffman<Vec<u8>, Vec<u16>> =
+            CanonicalHuffman::new_from_lengths(&lengths).unwrap();
+        for symbol in [0, 1, 2, 201, 202, 301].iter() {
+            let (code, len) = a.encode(*symbol).unwrap();
+            assert_eq!(len, lengths[*symbol as usize] as usize);
+            let bits: Vec<bool> =
+                (0..len).rev().map(|i| (code >> i) & 1 == 1).collect();
+            assert_eq!(decodeiter(&a, &bits), Some(*symbol));
+        }
+        assert_eq!(a.encode(302), None);
+
+        assert!(
+            CanonicalHuffman::<Vec<u8>>::new_from_lengths(&lengths).is_none()
+        );
+    }
+
+    #[test]
+    fn too_many_of_one_length() {
+        // a complete code, but 256 doesn't fit in a u8 count
+        let a = CanonicalHuffman::<Vec<u8>, Vec<u16>>::new_from_lengths(
+            &[8; 256],
+        );
+        assert_eq!(a, None);
+
+        // 255 does
+        let a = CanonicalHuffman::<Vec<u8>, Vec<u16>>::new_from_lengths(
+            &[8; 255],
+        );
+        assert!(a.is_some());
+    }
+
+    #[test]
+    fn decode_prefix() {
+        use crate::tables::{DISTANCE, LENGTH, LITERAL};
+
+        for table in [&LITERAL, &LENGTH, &DISTANCE].iter() {
+            for symbol in 0..=255 {
+                let (code, len) = match table.encode(symbol) {
+                    Some(v) => v,
+                    None => continue,
+                };
+
+                // pack the code least significant first, then some junk
+                let mut bits = 0b1011 << len;
+                let mut packed_bits = vec![];
+                for i in 0..len {
+                    let bit = (code >> (len - i - 1)) & 1;
+                    bits |= bit << i;
+                    packed_bits.push(bit == 1);
+                }
+
+                assert_eq!(decodeiter(table, &packed_bits), Some(symbol));
+                let mut d = table.decoder();
+                assert_eq!(
+                    d.decode_prefix(bits, len + 4),
+                    (DecodeResult::Ok(symbol), len)
+                );
+
+                // and in two pieces, after a reset
+                let mut d = table.decoder();
+                d.decode_prefix(!bits, len + 4);
+                d.reset();
+                assert_eq!(
+                    d.decode_prefix(bits, len - 1),
+                    (DecodeResult::Incomplete, len - 1)
+                );
+                assert_eq!(
+                    d.decode_prefix(bits >> (len - 1), 5),
+                    (DecodeResult::Ok(symbol), 1)
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,159 @@
+// edge cases that are easy to get wrong when porting blast.c
+//
+// each stream is built from a list of tokens, and checked against a
+// copy of it in compat/. the expected output next to it there was
+// produced by another implementation, not this crate: by pklib 0.2.0
+// (a port of Ladislav Zezula's PKLib), except for window_wrap, where
+// pklib drops output once a copy crosses 4096 bytes. that one was
+// checked against a byte-by-byte copy from a flat buffer instead.
+// to check again with zlib's blast, run each .z through it
+
+use crate::examples::BitWriter;
+use crate::{explode, explode_with_buffer};
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, Debug)]
+enum Token {
+    Literal(u8),
+    Copy { len: usize, dist: usize },
+}
+
+// build a stream from tokens
+fn build(lit: u8, dict: u8, tokens: &[Token]) -> Vec<u8> {
+    let mut out = BitWriter::new();
+    out.header(lit, dict);
+    for token in tokens.iter() {
+        match *token {
+            Token::Literal(b) if lit > 0 => out.literal_coded(b),
+            Token::Literal(b) => out.literal(b),
+            Token::Copy { len, dist } => out.copy(len, dist, dict),
+        }
+    }
+    out.end();
+    out.finish()
+}
+
+fn check(
+    lit: u8,
+    dict: u8,
+    tokens: &[Token],
+    stream: &[u8],
+    expected: &[u8],
+) {
+    let encoded = build(lit, dict, tokens);
+    assert_eq!(encoded, stream);
+    assert_eq!(explode(&encoded).unwrap(), expected);
+    let mut buf = [0; 1];
+    let ours = explode_with_buffer(&encoded, &mut buf).unwrap();
+    assert_eq!(ours, expected);
+}
+
+#[test]
+fn max_length() {
+    // 'x', then the longest possible copy, by hand
+    let encoded = [0x00, 0x04, 0xf0, 0x02, 0xfc, 0x87, 0x80, 0x7f];
+    assert_eq!(explode(&encoded).unwrap(), [b'x'; 519]);
+
+    use Token::*;
+    let tokens = [Literal(b'x'), Copy { len: 518, dist: 1 }];
+    check(0, 4, &tokens, &encoded, &[b'x'; 519]);
+}
+
+#[test]
+fn full_window() {
+    use Token::*;
+    let files: [(&[u8], &[u8]); 3] = [
+        (
+            include_bytes!("compat/full_window_4.z"),
+            include_bytes!("compat/full_window_4.out"),
+        ),
+        (
+            include_bytes!("compat/full_window_5.z"),
+            include_bytes!("compat/full_window_5.out"),
+        ),
+        (
+            include_bytes!("compat/full_window_6.z"),
+            include_bytes!("compat/full_window_6.out"),
+        ),
+    ];
+    for (dict, (stream, expected)) in (4..=6).zip(files.iter()) {
+        let size = 64 << dict;
+        let mut tokens: Vec<Token> = (0..size)
+            .map(|i| Literal((i * 7 + i / 256) as u8))
+            .collect();
+        tokens.push(Copy {
+            len: 518,
+            dist: size,
+        });
+        tokens.push(Copy { len: 2, dist: 256 });
+        check(0, dict, &tokens, stream, expected);
+    }
+}
+
+#[test]
+fn coded_high_literals() {
+    use Token::*;
+    let mut tokens: Vec<Token> = (0x80..=0xff).map(Literal).collect();
+    tokens.push(Copy { len: 3, dist: 128 });
+    tokens.push(Copy { len: 2, dist: 131 });
+    tokens.extend((0x00..0x80).rev().map(Literal));
+    check(
+        1,
+        5,
+        &tokens,
+        include_bytes!("compat/coded_high_literals.z"),
+        include_bytes!("compat/coded_high_literals.out"),
+    );
+}
+
+#[test]
+fn window_wrap() {
+    // long copies, after the window has filled and wrapped around
+    use Token::*;
+    let mut tokens: Vec<Token> = (b'a'..=b'j').map(Literal).collect();
+    tokens.push(Copy { len: 100, dist: 3 });
+    let mut have = 110;
+    for k in 0..40 {
+        let dist = [4096, 4000, 1, 2, 100][k % 5];
+        let len = 470 + k;
+        tokens.push(Copy {
+            len,
+            dist: std::cmp::min(dist, have),
+        });
+        have += len;
+    }
+    tokens.push(Copy { len: 2, dist: 1 });
+    check(
+        0,
+        6,
+        &tokens,
+        include_bytes!("compat/window_wrap.z"),
+        include_bytes!("compat/window_wrap.out"),
+    );
+}
+
+#[test]
+fn too_far_back() {
+    // one more than the window holds
+    use Token::*;
+    let mut tokens: Vec<Token> = (0..100).map(Literal).collect();
+    tokens.push(Copy { len: 3, dist: 101 });
+    assert_eq!(
+        explode(&build(0, 6, &tokens)),
+        Err(crate::Error::BadDistance {
+            distance: 101,
+            window_len: 100,
+        })
+    );
+
+    // but exactly as much is fine
+    tokens.pop();
+    tokens.push(Copy { len: 3, dist: 100 });
+    check(
+        0,
+        6,
+        &tokens,
+        include_bytes!("compat/too_far_back.z"),
+        include_bytes!("compat/too_far_back.out"),
+    );
+}
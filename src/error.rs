@@ -3,44 +3,125 @@
 /// Of these, `IncompleteInput` is special as in some circumstances it
 /// is possible to recover by providing further input. This is
 /// documented wherever it is possible.
+///
+/// Errors can be compared with `==`, which is useful in tests. Two
+/// `IO` errors compare equal if they have the same
+/// [`ErrorKind`][ErrorKind], even if their messages differ.
+///
+///  [ErrorKind]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html
+///
+/// ```
+/// let bytes = vec![0x00, 0x07];
+/// assert_eq!(explode::explode(&bytes), Err(explode::Error::BadDictionary(7)));
+/// ```
 #[derive(Debug)]
 pub enum Error {
     /// A normal IO error.
+    ///
+    /// This is only available with the `std` feature.
+    #[cfg(feature = "std")]
     IO(std::io::Error),
     /// The input is incomplete. Decompression may still succeed if
     /// you provide more input.
     IncompleteInput,
-    /// The literal flag in the header is invalid.
-    BadLiteralFlag,
-    /// The dictionary size in the header is invalid.
-    BadDictionary,
+    /// The literal flag in the header is invalid. This holds the flag
+    /// that was read.
+    BadLiteralFlag(u8),
+    /// The dictionary size in the header is invalid. This holds the
+    /// size that was read.
+    BadDictionary(u8),
     /// A repeat command tried to read past the beginning of the buffer.
-    BadDistance,
+    BadDistance {
+        /// The distance back that the command asked for.
+        distance: usize,
+        /// How much output is available to copy from.
+        window_len: usize,
+    },
+    /// The input does not match any code in a Huffman table. The
+    /// fixed tables cover every code, so this should never happen,
+    /// but it is returned instead of panicking.
+    InvalidCode,
+    /// A Huffman tree stored in PKZIP imploded data is invalid.
+    BadTree,
+    /// The output is longer than the limit set with
+    /// [`ExplodeConfig::output_limit`](struct.ExplodeConfig.html#method.output_limit).
+    OutputLimitExceeded,
+    /// The output does not fit in the fixed space given for it, by
+    /// [`explode_into`](fn.explode_into.html) or
+    /// [`explode_buf`](fn.explode_buf.html).
+    OutputFull,
 }
 
 /// Result type for decompression functions.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
-impl std::convert::From<std::io::Error> for Error {
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
     fn from(v: std::io::Error) -> Self {
         Error::IO(v)
     }
 }
 
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        use Error::*;
+        match (self, other) {
+            // io::Error isn't comparable, so settle for the kind
+            #[cfg(feature = "std")]
+            (IO(a), IO(b)) => a.kind() == b.kind(),
+            (IncompleteInput, IncompleteInput) => true,
+            (BadLiteralFlag(a), BadLiteralFlag(b)) => a == b,
+            (BadDictionary(a), BadDictionary(b)) => a == b,
+            (
+                BadDistance {
+                    distance: a,
+                    window_len: b,
+                },
+                BadDistance {
+                    distance: c,
+                    window_len: d,
+                },
+            ) => a == c && b == d,
+            (InvalidCode, InvalidCode) => true,
+            (BadTree, BadTree) => true,
+            (OutputLimitExceeded, OutputLimitExceeded) => true,
+            (OutputFull, OutputFull) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             Error::IO(err) => write!(f, "{}", err),
             Error::IncompleteInput => write!(f, "unexpected end of input"),
-            Error::BadLiteralFlag => {
-                write!(f, "literal flag not zero or one")
+            Error::BadLiteralFlag(lit) => {
+                write!(f, "literal flag {} not zero or one", lit)
+            }
+            Error::BadDictionary(dict) => {
+                write!(f, "dictionary size {} not in 4..=6", dict)
             }
-            Error::BadDictionary => write!(f, "dictionary size not in 4..=6"),
-            Error::BadDistance => write!(f, "distance is too far back"),
+            Error::BadDistance {
+                distance,
+                window_len,
+            } => write!(
+                f,
+                "distance {} exceeds window of {}",
+                distance, window_len
+            ),
+            Error::InvalidCode => write!(f, "invalid Huffman code"),
+            Error::BadTree => write!(f, "invalid stored Huffman tree"),
+            Error::OutputLimitExceeded => write!(f, "output limit exceeded"),
+            Error::OutputFull => write!(f, "output buffer is full"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -49,3 +130,82 @@ impl std::error::Error for Error {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn send_sync() {
+        // needed to use Error with anyhow, eyre, etc.
+        assert_send_sync::<Error>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn boxed() {
+        let boxed: Box<dyn std::error::Error + Send + Sync> =
+            Box::new(Error::IncompleteInput);
+        assert_eq!(boxed.to_string(), "unexpected end of input");
+
+        let err = Error::BadDistance {
+            distance: 101,
+            window_len: 100,
+        };
+        assert_eq!(err.to_string(), "distance 101 exceeds window of 100");
+        let boxed: Box<dyn std::error::Error + Send + Sync> = err.into();
+        assert!(boxed.downcast_ref::<Error>().is_some());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn source() {
+        use std::error::Error as _;
+        use std::io::{Error as IOError, ErrorKind};
+
+        let err = Error::from(IOError::from(ErrorKind::WriteZero));
+        let source = err.source().unwrap();
+        let io = source.downcast_ref::<IOError>().unwrap();
+        assert_eq!(io.kind(), ErrorKind::WriteZero);
+
+        assert!(Error::BadLiteralFlag(2).source().is_none());
+        assert_eq!(
+            Error::BadLiteralFlag(2).to_string(),
+            "literal flag 2 not zero or one"
+        );
+        assert_eq!(
+            Error::BadDictionary(0x78).to_string(),
+            "dictionary size 120 not in 4..=6"
+        );
+    }
+
+    #[test]
+    fn eq() {
+        assert_eq!(Error::BadTree, Error::BadTree);
+        assert_ne!(Error::BadTree, Error::InvalidCode);
+        assert_ne!(Error::OutputFull, Error::OutputLimitExceeded);
+        assert_ne!(Error::BadTree, Error::BadDictionary(7));
+        assert_ne!(Error::BadDictionary(7), Error::BadDictionary(8));
+        let bad = |distance| Error::BadDistance {
+            distance,
+            window_len: 1,
+        };
+        assert_eq!(bad(2), bad(2));
+        assert_ne!(bad(2), bad(3));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn eq_io() {
+        use std::io::{Error as IOError, ErrorKind};
+
+        // IO errors compare by kind only
+        let a = IOError::other("a");
+        let b = IOError::other("b");
+        assert_eq!(Error::IO(a), Error::IO(b));
+        let c = IOError::from(ErrorKind::UnexpectedEof);
+        assert_ne!(Error::IO(c), Error::IncompleteInput);
+    }
+}
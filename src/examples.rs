@@ -1,3 +1,10 @@
+#[cfg(test)]
+use crate::codes::CanonicalHuffman;
+#[cfg(test)]
+use crate::tables;
+#[cfg(test)]
+use alloc::{vec, vec::Vec};
+
 #[cfg(test)]
 pub const EXAMPLES: &[(&[u8], &[u8])] = &[
     (
@@ -8,8 +15,114 @@ pub const EXAMPLES: &[(&[u8], &[u8])] = &[
             0x49, 0x41,
         ],
     ),
+    (
+        // header and end code only, with no output
+        &[0x00, 0x04, 0x01, 0xff],
+        &[],
+    ),
     (
         include_bytes!("examples/undhr.z"),
         include_bytes!("examples/undhr.md"),
     ),
 ];
+
+// build a stream of uncoded literals, for tests that need specific output
+#[cfg(test)]
+pub fn uncoded(data: &[u8]) -> Vec<u8> {
+    let mut out = BitWriter::new();
+    out.header(0, 4);
+    for b in data.iter() {
+        out.literal(*b);
+    }
+    out.end();
+    out.finish()
+}
+
+// write bits, least significant first
+#[cfg(test)]
+pub struct BitWriter {
+    out: Vec<u8>,
+    bitbuf: u32,
+    bitcount: u8,
+}
+
+#[cfg(test)]
+impl BitWriter {
+    pub fn new() -> Self {
+        BitWriter {
+            out: vec![],
+            bitbuf: 0,
+            bitcount: 0,
+        }
+    }
+
+    pub fn bits(&mut self, value: u32, n: u8) {
+        self.bitbuf |= (value & ((1 << n) - 1)) << self.bitcount;
+        self.bitcount += n;
+        while self.bitcount >= 8 {
+            self.out.push(self.bitbuf as u8);
+            self.bitbuf >>= 8;
+            self.bitcount -= 8;
+        }
+    }
+
+    // number of bits written so far
+    pub fn position(&self) -> u64 {
+        self.out.len() as u64 * 8 + self.bitcount as u64
+    }
+
+    // write a Huffman code, inverted and most significant bit first
+    pub fn code(&mut self, table: &CanonicalHuffman<&[u8]>, symbol: u8) {
+        let (code, len) = table.encode(symbol).unwrap();
+        for i in (0..len).rev() {
+            self.bits(!(code >> i), 1);
+        }
+    }
+
+    pub fn header(&mut self, lit: u8, dict: u8) {
+        self.bits(lit as u32, 8);
+        self.bits(dict as u32, 8);
+    }
+
+    pub fn literal(&mut self, value: u8) {
+        self.bits(0, 1);
+        self.bits(value as u32, 8);
+    }
+
+    pub fn literal_coded(&mut self, value: u8) {
+        self.bits(0, 1);
+        self.code(&tables::LITERAL, value);
+    }
+
+    // copy len bytes from dist back, for the given dictionary size
+    pub fn copy(&mut self, len: usize, dist: usize, dict: u8) {
+        let symbol = (0..16)
+            .find(|s| {
+                let base = tables::LEN_BASE[*s];
+                base <= len && len < base + (1 << tables::LEN_EXTRA[*s])
+            })
+            .unwrap();
+        self.bits(1, 1);
+        self.code(&tables::LENGTH, symbol as u8);
+        let extra = tables::LEN_EXTRA[symbol];
+        self.bits((len - tables::LEN_BASE[symbol]) as u32, extra);
+
+        let extra = if len == 2 { 2 } else { dict };
+        self.code(&tables::DISTANCE, ((dist - 1) >> extra) as u8);
+        self.bits((dist - 1) as u32, extra);
+    }
+
+    // length symbol 15 with all extra bits set is the end code
+    pub fn end(&mut self) {
+        self.bits(1, 1);
+        self.code(&tables::LENGTH, 15);
+        self.bits(0xff, 8);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        if self.bitcount > 0 {
+            self.out.push(self.bitbuf as u8);
+        }
+        self.out
+    }
+}
@@ -1,7 +1,16 @@
-use super::codes::{DecodeResult, Decoder};
+use super::codes::{DecodeResult, Decoder, FastTable, FAST_BITS};
+#[cfg(feature = "std")]
+use super::reader::ReadAdapter;
 use super::{tables, Error, Result};
+#[cfg(feature = "std")]
+use super::{Checksum, ExplodeReader, NoChecksum};
 
-use arraydeque::ArrayDeque;
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use std::io::{ErrorKind, Read, Write};
+
+use super::window::{Window, WINDOW_SIZE};
 
 /// Low-level decompression interface.
 ///
@@ -10,7 +19,35 @@ use arraydeque::ArrayDeque;
 /// [`ExplodeReader`](struct.ExplodeReader.html) as they are simpler
 /// to use.
 ///
-/// The usual control flow with this interface is to provide a buffer
+/// The simplest way to use this interface is with
+/// [`feed_slice_into`](#method.feed_slice_into), which decompresses
+/// as much of an input slice as it can into an output slice, and
+/// reports how much of each was used.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// use explode::Explode;
+///
+/// let input = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let mut outbuf: [u8; 4] = [0; 4];
+/// let mut decompressed = vec![];
+///
+/// let mut ex = Explode::new();
+/// let mut i = 0;
+/// while !ex.done() {
+///     let (used, len) = ex.feed_slice_into(&input[i..], &mut outbuf)?;
+///     if used == 0 && len == 0 {
+///         // we ran out of input, but decompression isn't done!
+///         panic!("unexpected end of input");
+///     }
+///     i += used;
+///     decompressed.extend_from_slice(&outbuf[..len]);
+/// }
+/// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+///
+/// For complete control, provide a buffer
 /// to decompress into with [`with_buffer`](#method.with_buffer), and
 /// then to feed the resulting
 /// [`ExplodeBuffer`](struct.ExplodeBuffer.html) handle with bytes
@@ -67,10 +104,35 @@ use arraydeque::ArrayDeque;
 /// only changes when requested by
 /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput). If
 /// the input changes at any other time, decompression will fail or
-/// produce incorrect output.
+/// produce incorrect output. You can check whether the next call to
+/// `feed` will use a new byte with
+/// [`ExplodeBuffer::wants_input`](struct.ExplodeBuffer.html#method.wants_input),
+/// or avoid the problem entirely by feeding whole slices with
+/// [`ExplodeBuffer::feed_slice`](struct.ExplodeBuffer.html#method.feed_slice),
+/// or iterators with
+/// [`ExplodeBuffer::feed_from`](struct.ExplodeBuffer.html#method.feed_from).
+///
+/// An `Explode` can be copied at any point with
+/// [`try_clone`](#method.try_clone), and the copy continues from
+/// exactly the same place. This is useful to try decoding data that
+/// might not be valid, and roll back if it fails. Callbacks can only
+/// be copied if they were set with
+/// [`on_checkpoint_cloneable`](#method.on_checkpoint_cloneable) or
+/// [`with_output_transform_cloneable`](#method.with_output_transform_cloneable).
+///
+/// The window of recent output that copies refer back to is 4096
+/// bytes, and is stored inside the `Explode` by default. To keep it
+/// somewhere else, such as in a pool shared by many decompressors,
+/// use [`with_window`](#method.with_window). The storage type is the
+/// parameter `S`.
 #[derive(Debug)]
-pub struct Explode {
-    state: ExplodeState<Decoder<'static, &'static [u8]>>,
+pub struct Explode<S = [u8; WINDOW_SIZE]> {
+    state: ExplodeState,
+
+    // Huffman decoders, reused for every symbol
+    length: Decoder<'static, &'static [u8]>,
+    distance: Decoder<'static, &'static [u8]>,
+    literal: Decoder<'static, &'static [u8]>,
 
     // header info
     lit: Option<u8>,
@@ -78,13 +140,251 @@ pub struct Explode {
 
     // input management
     input: ExplodeInput,
+    // input.consumed when the current token started
+    token_start: u64,
+    // bytes, then bits to skip before the header
+    skip_bytes: usize,
+    skip_bits: u8,
+
+    // why decoding stopped, once it has
+    end_reason: Option<EndReason>,
+
+    // bytes of output produced so far
+    total_out: u64,
+    // callback from on_checkpoint
+    checkpoints: Option<CheckpointHook>,
+    // callback from with_output_transform
+    transform: Option<(TransformOrder, OutputTransform)>,
+
+    // the configuration we started with, for reset()
+    config: ExplodeConfig,
 
     // store our window (which cannot exceed 4096 bytes)
-    window: ArrayDeque<[u8; 4096], arraydeque::behavior::Wrapping>,
+    window: Window<S>,
+}
+
+/// Configuration for an [`Explode`](struct.Explode.html)
+/// decompressor.
+///
+/// The default configuration will decompress ordinary implode
+/// streams. Use the builder-style methods here to change that, and
+/// pass the result to [`Explode::new_with`](struct.Explode.html#method.new_with).
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// use explode::{Explode, ExplodeConfig};
+///
+/// // parse a stream with the two header bytes stripped off
+/// let config = ExplodeConfig::new().headerless(false, 4);
+/// let ex = Explode::new_with(config);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ExplodeConfig {
+    // preset header, as (lit, dict)
+    header: Option<(u8, u8)>,
+    tolerant: bool,
+    bit_offset: u8,
+    capacity_hint: usize,
+    output_limit: Option<u64>,
+    // preset window contents
+    dictionary: Vec<u8>,
+}
+
+/// The parsed header of a compressed stream.
+///
+/// This is returned by
+/// [`Explode::header`](struct.Explode.html#method.header) and
+/// [`ExplodeReader::read_header`](struct.ExplodeReader.html#method.read_header).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// True if literals are Huffman coded. This is usually used for
+    /// text, and uncoded literals for binary data.
+    pub coded_literals: bool,
+    /// The number of extra bits in distances, between 4 and 6
+    /// inclusive.
+    pub dict_size: u8,
+}
+
+impl Header {
+    /// The size of the dictionary in bytes: 1024, 2048, or 4096.
+    pub fn window_size(&self) -> usize {
+        64 << self.dict_size
+    }
+}
+
+/// The reason decompression stopped.
+///
+/// This is returned by
+/// [`Explode::end_reason`](struct.Explode.html#method.end_reason) once
+/// decompression is finished. More reasons may be added later, so
+/// matches on this need a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EndReason {
+    /// The stream ended with an end code.
+    EndCode,
+    /// The input ran out cleanly between two tokens, and the
+    /// decompressor was configured with
+    /// [`ExplodeConfig::tolerant`](struct.ExplodeConfig.html#method.tolerant).
+    MissingEndCode,
+}
+
+/// What a decompressor is doing, as returned by
+/// [`Explode::phase`](struct.Explode.html#method.phase).
+///
+/// This is useful to report where decompression stopped, for example
+/// on a truncated stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Reading the stream header.
+    Header,
+    /// Reading a literal, or the start of the next token.
+    Literal,
+    /// Reading the length of a copy, or the end code.
+    Length,
+    /// Reading the distance of a copy.
+    Distance,
+    /// Copying from earlier output.
+    Copy,
+    /// Finished.
+    Done,
+}
+
+/// Why a decompressor stopped, as returned by
+/// [`Explode::status`](struct.Explode.html#method.status).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// The end of the stream was reached.
+    Finished,
+    /// All of the input so far is used, and more is needed to
+    /// continue. This is also the status before any input.
+    NeedsInput,
+    /// The output buffer filled up, and decompression can continue
+    /// with more room before any more input is needed.
+    InProgress,
+}
+
+/// A saved position in a compressed stream, to restart decompression
+/// from.
+///
+/// These are produced by
+/// [`Explode::on_checkpoint`](struct.Explode.html#method.on_checkpoint),
+/// and used with
+/// [`Explode::restore_state`](struct.Explode.html#method.restore_state).
+/// All of the fields are public, so checkpoints can be stored
+/// however is convenient.
+///
+/// Each checkpoint holds a copy of the dictionary, so it takes up to
+/// [`Header::window_size`](struct.Header.html#method.window_size)
+/// bytes, plus a few more for the other fields. For a stream with a
+/// 4K dictionary, a checkpoint every 64K of output costs about 1/16th
+/// of the decompressed size.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// How many bytes of output came before this checkpoint.
+    pub output_position: u64,
+    /// The input position of the next token, as returned by
+    /// [`Explode::bit_position`](struct.Explode.html#method.bit_position).
+    pub bit_position: u64,
+    /// The stream header.
+    pub header: Header,
+    /// The most recent output, up to the dictionary size.
+    pub window: Vec<u8>,
+}
+
+// a boxed closure that can be cloned, so Explode can be too. it is
+// only ever called through &mut, so it need not be Sync
+trait CloneFn<A, R>: FnMut(A) -> R + Send {
+    fn clone_box(&self) -> Box<dyn CloneFn<A, R>>;
+}
+
+impl<A, R, F> CloneFn<A, R> for F
+where
+    F: FnMut(A) -> R + Clone + Send + 'static,
+{
+    fn clone_box(&self) -> Box<dyn CloneFn<A, R>> {
+        Box::new(self.clone())
+    }
+}
+
+// a callback set by the user, which can only be cloned if it was set
+// with one of the *_cloneable methods
+enum Hook<A, R> {
+    Plain(Box<dyn FnMut(A) -> R + Send>),
+    Cloneable(Box<dyn CloneFn<A, R>>),
+}
+
+impl<A, R> Hook<A, R> {
+    #[inline]
+    fn call(&mut self, arg: A) -> R {
+        match self {
+            Hook::Plain(f) => f(arg),
+            Hook::Cloneable(f) => f(arg),
+        }
+    }
+
+    // None if this was not set with a *_cloneable method
+    fn try_clone(&self) -> Option<Self> {
+        match self {
+            Hook::Plain(_) => None,
+            Hook::Cloneable(f) => Some(Hook::Cloneable(f.clone_box())),
+        }
+    }
+}
+
+// a callback from on_checkpoint, and when to call it next
+struct CheckpointHook {
+    interval: u64,
+    next: u64,
+    f: Hook<Checkpoint, ()>,
+}
+
+impl CheckpointHook {
+    fn try_clone(&self) -> Option<Self> {
+        Some(CheckpointHook {
+            interval: self.interval,
+            next: self.next,
+            f: self.f.try_clone()?,
+        })
+    }
+}
+
+impl core::fmt::Debug for CheckpointHook {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("CheckpointHook")
+            .field("interval", &self.interval)
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+/// When to apply an output transform, relative to the dictionary.
+///
+/// This is used with
+/// [`Explode::with_output_transform`](struct.Explode.html#method.with_output_transform).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransformOrder {
+    /// Transform each byte before it is added to the dictionary, so
+    /// later copies see transformed bytes, and are transformed again.
+    BeforeWindow,
+    /// Transform each byte after it is added to the dictionary, so
+    /// only the output is changed. This is what is needed to unmask
+    /// output that was masked after compression.
+    AfterWindow,
+}
+
+// a callback from with_output_transform
+struct OutputTransform(Hook<u8, u8>);
+
+impl core::fmt::Debug for OutputTransform {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("OutputTransform").finish_non_exhaustive()
+    }
 }
 
 // hold a byte until it's ready to use
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum ExplodeInputState {
     Available(u8),
     Taken,
@@ -92,26 +392,29 @@ enum ExplodeInputState {
 }
 
 // help manage the bitstream input
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct ExplodeInput {
     next: ExplodeInputState,
 
     // store unused bits read in
     bitbuf: u32,
     bitcount: u8,
+
+    // total bytes taken so far
+    consumed: u64,
 }
 
-// explode state. D is the Huffman decoder type
-#[derive(Debug)]
-enum ExplodeState<D> {
+// explode state. states that decode a symbol use the matching decoder
+#[derive(Clone, Debug)]
+enum ExplodeState {
     Start,
-    Length { decoder: D },
+    Length,
     LengthExtra { symbol: usize },
-    Distance { len: usize, decoder: D },
+    Distance { len: usize },
     DistanceExtra { len: usize, symbol: usize },
     Copy { idx: usize, len: usize },
     Literal,
-    LiteralCoded { decoder: D },
+    LiteralCoded,
     End,
 }
 
@@ -124,19 +427,62 @@ enum ExplodeState<D> {
 /// For a high-level example of how to use this interface, see
 /// [`Explode`](struct.Explode.html).
 #[derive(Debug)]
-pub struct ExplodeBuffer<'a> {
-    parent: &'a mut Explode,
-    buf: &'a mut [u8],
+pub struct ExplodeBuffer<'a, S = [u8; WINDOW_SIZE]> {
+    parent: &'a mut Explode<S>,
+    // only buf[..pos] is initialized
+    buf: &'a mut [MaybeUninit<u8>],
     pos: usize,
 }
 
+// view an output buffer as possibly uninitialized. this is only safe
+// because nothing ever writes uninitialized bytes through the result
+#[inline]
+pub(crate) fn as_uninit(buf: &mut [u8]) -> &mut [MaybeUninit<u8>] {
+    // SAFETY: MaybeUninit<u8> has the same layout as u8
+    unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) }
+}
+
+// view output that has been written as bytes
+//
+// SAFETY: all of buf must be initialized
+#[inline]
+pub(crate) unsafe fn assume_init(buf: &[MaybeUninit<u8>]) -> &[u8] {
+    &*(buf as *const [MaybeUninit<u8>] as *const [u8])
+}
+
+// copy src into the start of dst
+#[inline]
+fn write_slice(dst: &mut [MaybeUninit<u8>], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d = MaybeUninit::new(*s);
+    }
+}
+
+// how often to report progress, in bytes of output
+#[cfg(feature = "tracing")]
+const PROGRESS_INTERVAL: u64 = 1 << 20;
+
+// add n bytes to the output count, reporting progress now and then
+#[inline]
+fn count_out(total_out: &mut u64, n: u64) {
+    #[cfg(feature = "tracing")]
+    let before = *total_out / PROGRESS_INTERVAL;
+    *total_out += n;
+    #[cfg(feature = "tracing")]
+    if *total_out / PROGRESS_INTERVAL != before {
+        event!(DEBUG, total_out = *total_out, "progress");
+    }
+}
+
 impl ExplodeInputState {
+    #[inline]
     fn feed(&mut self, value: u8) {
         if let ExplodeInputState::Waiting = self {
             *self = ExplodeInputState::Available(value);
         }
     }
 
+    #[inline]
     fn take(&mut self) -> Result<u8> {
         match self {
             ExplodeInputState::Available(value) => {
@@ -144,22 +490,31 @@ impl ExplodeInputState {
                 *self = ExplodeInputState::Taken;
                 Ok(v)
             }
-            ExplodeInputState::Taken => {
+            // still waiting is the same as having nothing, so misuse
+            // of feed can't get stuck or panic here
+            ExplodeInputState::Taken | ExplodeInputState::Waiting => {
                 *self = ExplodeInputState::Waiting;
+                event!(TRACE, "waiting for input");
                 Err(Error::IncompleteInput)
             }
-            ExplodeInputState::Waiting => {
-                panic!("double take");
-            }
         }
     }
 }
 
 impl ExplodeInput {
+    // take the next byte of input
+    #[inline]
+    fn byte(&mut self) -> Result<u32> {
+        let v = self.next.take()?;
+        self.consumed += 1;
+        Ok(v as u32)
+    }
+
     // read n bits
+    #[inline]
     fn bits(&mut self, n: u8) -> Result<u32> {
         while self.bitcount < n {
-            self.bitbuf |= (self.next.take()? as u32) << self.bitcount;
+            self.bitbuf |= self.byte()? << self.bitcount;
             self.bitcount += 8;
         }
 
@@ -170,23 +525,162 @@ impl ExplodeInput {
         Ok(val & ((1 << n) - 1))
     }
 
-    // decode using a table
-    fn decode(&mut self, d: &mut Decoder<&'static [u8]>) -> Result<u8> {
+    // decode using a table, and its lookup table for short codes
+    fn decode(
+        &mut self,
+        d: &mut Decoder<&'static [u8]>,
+        fast: &FastTable,
+    ) -> Result<u8> {
         loop {
+            if self.bitcount == 0 {
+                self.bitbuf = self.byte()?;
+                self.bitcount = 8;
+            }
+
+            // short codes are looked up all at once, at the start of a
+            // code. if no short code matches the bits read so far, the
+            // code is longer, so it's safe to read another byte first
+            if d.is_reset() {
+                let available = self.bitcount as usize;
+                if let Some((v, used)) = fast.lookup(self.bitbuf, available) {
+                    self.bitbuf >>= used;
+                    self.bitcount -= used as u8;
+                    return Ok(v);
+                }
+                if available < FAST_BITS {
+                    self.bitbuf |= self.byte()? << self.bitcount;
+                    self.bitcount += 8;
+                    continue;
+                }
+            }
+
             // codes in this format are inverted from canonical
-            let bit = self.bits(1)? != 1;
-            match d.feed(bit) {
+            let (result, used) =
+                d.decode_prefix(!self.bitbuf, self.bitcount as usize);
+            self.bitbuf >>= used;
+            self.bitcount -= used as u8;
+
+            match result {
                 DecodeResult::Incomplete => continue,
-                DecodeResult::Invalid => panic!(
-                    "Codebooks are under-subscribed but should not be!"
-                ),
+                DecodeResult::Invalid => {
+                    event!(WARN, "invalid code");
+                    return Err(Error::InvalidCode);
+                }
                 DecodeResult::Ok(v) => return Ok(v),
             }
         }
     }
 }
 
-impl<'a> ExplodeBuffer<'a> {
+impl<'a, S> ExplodeBuffer<'a, S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    // decode a run of uncoded literals, in the Start or Literal states
+    // this asks for input exactly when the full state machine would
+    #[inline]
+    fn literal_run(&mut self) -> Result<()> {
+        let parent = &mut *self.parent;
+        let input = &mut parent.input;
+        while self.pos < self.buf.len() {
+            if let ExplodeState::Start = parent.state {
+                if input.bitcount == 0 {
+                    input.bitbuf = input.byte()?;
+                    input.bitcount = 8;
+                }
+
+                if input.bitbuf & 1 != 0 {
+                    // a length/distance pair, leave it to the state machine
+                    break;
+                }
+                input.bitbuf >>= 1;
+                input.bitcount -= 1;
+                parent.token_start = input.consumed;
+                parent.state = ExplodeState::Literal;
+            }
+
+            parent.config.check_limit(parent.total_out)?;
+            let value = input.bits(8)? as u8;
+            parent.state = ExplodeState::Start;
+            parent.window.push_back(value);
+            count_out(&mut parent.total_out, 1);
+            self.buf[self.pos] = MaybeUninit::new(value);
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    // copy up to len bytes starting at window index idx, as far as the
+    // output buffer and output limit allow, and advance idx and len.
+    // transforms are not applied, so only use this without one
+    fn copy_run(&mut self, idx: &mut usize, len: &mut usize) {
+        let parent = &mut *self.parent;
+        let mut n = (*len).min(self.buf.len() - self.pos);
+        if let Some(limit) = parent.config.output_limit {
+            let left = limit.saturating_sub(parent.total_out);
+            n = left.min(n as u64) as usize;
+        }
+        if n == 0 {
+            return;
+        }
+
+        let dist = parent.window.len() - *idx;
+        let out = &mut self.buf[self.pos..self.pos + n];
+
+        // first, whatever is already in the window
+        let first = n.min(dist);
+        let (a, b) = parent.window.as_slices();
+        if *idx >= a.len() {
+            let start = *idx - a.len();
+            write_slice(&mut out[..first], &b[start..start + first]);
+        } else {
+            let from_a = first.min(a.len() - *idx);
+            write_slice(&mut out[..from_a], &a[*idx..*idx + from_a]);
+            write_slice(&mut out[from_a..first], &b[..first - from_a]);
+        }
+
+        // then repeat it when dist < n. the output so far repeats
+        // every dist bytes, so copy whole repeats, doubling each time
+        let mut i = first;
+        while i < n {
+            let back = i - i % dist;
+            let amt = back.min(n - i);
+            out.copy_within(i - back..i - back + amt, i);
+            i += amt;
+        }
+
+        // SAFETY: all n bytes were written above
+        parent.window.extend_from_slice(unsafe { assume_init(out) });
+        count_out(&mut parent.total_out, n as u64);
+        self.pos += n;
+        *len -= n;
+        *idx = parent.window.len() - dist;
+    }
+
+    // add a decoded byte to the window and the output
+    #[inline]
+    fn emit(&mut self, value: u8) {
+        let parent = &mut *self.parent;
+        let out = match parent.transform {
+            None => {
+                parent.window.push_back(value);
+                value
+            }
+            Some((TransformOrder::BeforeWindow, ref mut f)) => {
+                let value = f.0.call(value);
+                parent.window.push_back(value);
+                value
+            }
+            Some((TransformOrder::AfterWindow, ref mut f)) => {
+                parent.window.push_back(value);
+                f.0.call(value)
+            }
+        };
+        count_out(&mut parent.total_out, 1);
+        self.buf[self.pos] = MaybeUninit::new(out);
+        self.pos += 1;
+    }
+
     /// Feed in a byte `input` to decompress.
     ///
     /// Signals a full output buffer by returning `Ok(())`. You can
@@ -201,21 +695,40 @@ impl<'a> ExplodeBuffer<'a> {
     /// Doing anything else will result in a decompression failure or
     /// bad output.
     pub fn feed(&mut self, input: u8) -> Result<()> {
-        // lengths are funny -- base val + extra bits
-        static LEN_BASE: &[usize] =
-            &[3, 2, 4, 5, 6, 7, 8, 9, 10, 12, 16, 24, 40, 72, 136, 264];
-        static LEN_EXTRA: &[u8] =
-            &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8];
-
         self.parent.input.next.feed(input);
 
+        // skip any bytes and bits before the stream starts
+        while self.parent.skip_bytes > 0 {
+            self.parent.input.byte()?;
+            self.parent.skip_bytes -= 1;
+        }
+        if self.parent.skip_bits > 0 {
+            self.parent.input.bits(self.parent.skip_bits)?;
+            self.parent.skip_bits = 0;
+        }
+
+        // fast path for uncoded literals, once the header is parsed
+        // this skips token boundaries and transforms, so not with those
+        if self.parent.lit == Some(0)
+            && self.parent.dict.is_some()
+            && self.parent.checkpoints.is_none()
+            && self.parent.transform.is_none()
+            && matches!(
+                self.parent.state,
+                ExplodeState::Start | ExplodeState::Literal
+            )
+        {
+            self.literal_run()?;
+        }
+
         // first byte is 0 if literals are uncoded, or 1 if coded
         let lit = if let Some(lit) = self.parent.lit {
             lit
         } else {
             let lit = self.parent.input.bits(8)? as u8;
             if lit > 1 {
-                return Err(Error::BadLiteralFlag);
+                event!(WARN, lit, "bad literal flag");
+                return Err(Error::BadLiteralFlag(lit));
             }
             self.parent.lit = Some(lit);
             lit
@@ -227,63 +740,74 @@ impl<'a> ExplodeBuffer<'a> {
             dict
         } else {
             let dict = self.parent.input.bits(8)? as u8;
-            if dict < 4 || dict > 6 {
-                return Err(Error::BadDictionary);
+            if !(4..=6).contains(&dict) {
+                event!(WARN, dict, "bad dictionary size");
+                return Err(Error::BadDictionary(dict));
             }
             self.parent.dict = Some(dict);
+            event!(DEBUG, lit, dict, "parsed header");
             dict
         };
 
         // decode literals and length/distance pairs
         // state machine rules:
-        // each state may only call bits() once
-        // and decode() must store the HuffmanExplode in the state
+        // each state may only call bits() or decode() once
+        // and decoders must be reset before entering their state
         loop {
             use ExplodeState::*;
             match self.parent.state {
                 Start => {
-                    if self.parent.input.bits(1)? > 0 {
+                    self.parent.checkpoint();
+                    let flag = self.parent.input.bits(1)?;
+                    self.parent.token_start = self.parent.input.consumed;
+                    if flag > 0 {
                         // this is a length/distance pair. length first.
-                        self.parent.state = Length {
-                            decoder: tables::LENGTH.decoder(),
-                        };
+                        self.parent.length.reset();
+                        self.parent.state = Length;
                     } else {
                         // this is a literal
                         if lit > 0 {
-                            self.parent.state = LiteralCoded {
-                                decoder: tables::LITERAL.decoder(),
-                            };
+                            self.parent.literal.reset();
+                            self.parent.state = LiteralCoded;
                         } else {
                             self.parent.state = Literal;
                         }
                     }
                 }
 
-                Length { ref mut decoder } => {
-                    let symbol = self.parent.input.decode(decoder)? as usize;
+                Length => {
+                    let decoder = &mut self.parent.length;
+                    let symbol = self
+                        .parent
+                        .input
+                        .decode(decoder, &tables::LENGTH_FAST)?
+                        as usize;
                     self.parent.state = LengthExtra { symbol };
                 }
 
                 LengthExtra { symbol } => {
-                    let len = LEN_BASE[symbol]
-                        + self.parent.input.bits(LEN_EXTRA[symbol])? as usize;
+                    let len = tables::LEN_BASE[symbol]
+                        + self.parent.input.bits(tables::LEN_EXTRA[symbol])?
+                            as usize;
                     if len == 519 {
                         // end code
+                        event!(DEBUG, "reached end code");
                         self.parent.state = End;
+                        self.parent.end_reason = Some(EndReason::EndCode);
                     } else {
                         // distance next
-                        self.parent.state = Distance {
-                            len,
-                            decoder: tables::DISTANCE.decoder(),
-                        };
+                        self.parent.distance.reset();
+                        self.parent.state = Distance { len };
                     }
                 }
 
-                Distance {
-                    len,
-                    ref mut decoder,
-                } => {
-                    let symbol = self.parent.input.decode(decoder)? as usize;
+                Distance { len } => {
+                    let decoder = &mut self.parent.distance;
+                    let symbol = self
+                        .parent
+                        .input
+                        .decode(decoder, &tables::DISTANCE_FAST)?
+                        as usize;
                     self.parent.state = DistanceExtra { len, symbol };
                 }
 
@@ -293,9 +817,20 @@ impl<'a> ExplodeBuffer<'a> {
                         self.parent.input.bits(extra_bits)? as usize + 1;
                     dist += symbol << extra_bits;
 
+                    // distances can't exceed the dictionary size in the
+                    // header, but they can reach back past the start
                     if dist > self.parent.window.len() {
                         // too far back
-                        return Err(Error::BadDistance);
+                        event!(
+                            WARN,
+                            dist,
+                            window = self.parent.window.len(),
+                            "distance too far back"
+                        );
+                        return Err(Error::BadDistance {
+                            distance: dist,
+                            window_len: self.parent.window.len(),
+                        });
                     }
 
                     self.parent.state = Copy {
@@ -304,25 +839,27 @@ impl<'a> ExplodeBuffer<'a> {
                     };
                 }
 
-                Copy {
-                    ref mut idx,
-                    ref mut len,
-                } => {
-                    while *len > 0 {
+                Copy { mut idx, mut len } => {
+                    // without a transform, copy as much as fits at once
+                    if self.parent.transform.is_none() {
+                        self.copy_run(&mut idx, &mut len);
+                    }
+                    while len > 0 {
                         if self.pos >= self.buf.len() {
                             // not enough room
+                            event!(TRACE, len = self.pos, "output full");
+                            self.parent.state = Copy { idx, len };
                             return Ok(());
                         }
 
-                        let value = self.parent.window[*idx];
-                        *len -= 1;
+                        let parent = &*self.parent;
+                        parent.config.check_limit(parent.total_out)?;
+                        let value = self.parent.window[idx];
+                        len -= 1;
                         if !self.parent.window.is_full() {
-                            *idx += 1;
+                            idx += 1;
                         }
-
-                        self.parent.window.push_back(value);
-                        self.buf[self.pos] = value;
-                        self.pos += 1;
+                        self.emit(value);
                     }
                     self.parent.state = Start;
                 }
@@ -330,24 +867,28 @@ impl<'a> ExplodeBuffer<'a> {
                 Literal => {
                     if self.pos >= self.buf.len() {
                         // not enough room
+                        event!(TRACE, len = self.pos, "output full");
                         return Ok(());
                     }
+                    let parent = &*self.parent;
+                    parent.config.check_limit(parent.total_out)?;
                     let value = self.parent.input.bits(8)? as u8;
-                    self.parent.window.push_back(value);
-                    self.buf[self.pos] = value;
-                    self.pos += 1;
+                    self.emit(value);
                     self.parent.state = Start;
                 }
 
-                LiteralCoded { ref mut decoder } => {
+                LiteralCoded => {
                     if self.pos >= self.buf.len() {
                         // not enough room
+                        event!(TRACE, len = self.pos, "output full");
                         return Ok(());
                     }
-                    let value = self.parent.input.decode(decoder)?;
-                    self.parent.window.push_back(value);
-                    self.buf[self.pos] = value;
-                    self.pos += 1;
+                    let parent = &*self.parent;
+                    parent.config.check_limit(parent.total_out)?;
+                    let decoder = &mut self.parent.literal;
+                    let fast = &tables::LITERAL_FAST;
+                    let value = self.parent.input.decode(decoder, fast)?;
+                    self.emit(value);
                     self.parent.state = Start;
                 }
 
@@ -358,21 +899,131 @@ impl<'a> ExplodeBuffer<'a> {
         }
     }
 
+    /// Feed in as much of `input` as possible to decompress.
+    ///
+    /// This returns the number of bytes of `input` used. It stops
+    /// when the output buffer is full, when decompression is
+    /// finished, or when all of `input` has been used. Unlike
+    /// [`feed`](#method.feed), each byte is provided only once, and
+    /// running out of input is not an error: call this again with the
+    /// rest of the input, after [`reset`](#method.reset) if the
+    /// output buffer is full.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut outbuf = [0; 4];
+    /// let mut decompressed = vec![];
+    ///
+    /// let mut ex = explode::Explode::new();
+    /// let mut exbuf = ex.with_buffer(&mut outbuf);
+    /// let mut i = 0;
+    /// while !exbuf.done() {
+    ///     i += exbuf.feed_slice(&bytes[i..])?;
+    ///     decompressed.extend_from_slice(exbuf.get());
+    ///     exbuf.reset();
+    /// }
+    /// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+    /// # Ok(()) }
+    /// ```
+    pub fn feed_slice(&mut self, input: &[u8]) -> Result<usize> {
+        let mut input = input.iter().copied();
+        let start = input.len();
+        self.feed_from(&mut input)?;
+        Ok(start - input.len())
+    }
+
+    /// Feed in bytes from `input`, taking each one only when it is
+    /// needed.
+    ///
+    /// This stops when the output buffer is full, when decompression
+    /// is finished, or when `input` runs out. It returns `true` in the
+    /// first two cases, and `false` if more input is needed. As with
+    /// [`feed_slice`](#method.feed_slice), each byte is provided only
+    /// once, and no byte is taken from `input` that is not used.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut input = bytes.into_iter();
+    /// let mut outbuf = [0; 4];
+    /// let mut decompressed = vec![];
+    ///
+    /// let mut ex = explode::Explode::new();
+    /// let mut exbuf = ex.with_buffer(&mut outbuf);
+    /// while exbuf.feed_from(&mut input)? && !exbuf.done() {
+    ///     decompressed.extend_from_slice(exbuf.get());
+    ///     exbuf.reset();
+    /// }
+    /// decompressed.extend_from_slice(exbuf.get());
+    /// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+    /// # Ok(()) }
+    /// ```
+    pub fn feed_from<I>(&mut self, input: &mut I) -> Result<bool>
+    where
+        I: Iterator<Item = u8>,
+    {
+        while !self.done() {
+            // bytes are ignored unless the decompressor wants input
+            let byte = if self.wants_input() {
+                match input.next() {
+                    Some(byte) => byte,
+                    None => return Ok(false),
+                }
+            } else {
+                0
+            };
+
+            match self.feed(byte) {
+                Ok(()) => break,
+                Err(Error::IncompleteInput) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+
     /// Get a reference to the filled portion of the output buffer.
     ///
     /// This is usually called after [`feed`](#method.feed) returns `Ok(())`.
+    /// Before any output, and after [`reset`](#method.reset), this is
+    /// an empty slice.
     pub fn get(&self) -> &[u8] {
-        &self.buf[..self.pos]
+        // SAFETY: everything before pos has been written
+        unsafe { assume_init(&self.buf[..self.pos]) }
     }
 
     /// Return the amount of output produced so far.
+    ///
+    /// This is always the length of [`get`](#method.get).
     pub fn len(&self) -> usize {
         self.pos
     }
 
+    /// Returns true if no output has been produced yet.
+    ///
+    /// This is the same as `get().is_empty()`, and is true again
+    /// after [`reset`](#method.reset).
+    pub fn is_empty(&self) -> bool {
+        self.pos == 0
+    }
+
+    /// Returns true if the next call to [`feed`](#method.feed) will
+    /// use a new input byte.
+    ///
+    /// This is true after `feed` returns
+    /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput),
+    /// and false when `feed` must be given the same byte again.
+    pub fn wants_input(&self) -> bool {
+        matches!(self.parent.input.next, ExplodeInputState::Waiting)
+    }
+
     /// Reset the output buffer to empty.
     ///
-    /// Note that this does *not* reset the entire decompressor state.
+    /// Output is written from the start of the buffer again, and
+    /// [`get`](#method.get) returns an empty slice until there is
+    /// more. Note that this does *not* reset the entire decompressor
+    /// state.
     pub fn reset(&mut self) {
         self.pos = 0;
     }
@@ -385,161 +1036,3263 @@ impl<'a> ExplodeBuffer<'a> {
     pub fn done(&self) -> bool {
         self.parent.done()
     }
+
+    /// Panic if the internal state is inconsistent.
+    ///
+    /// This is only available in tests, or with the `debug-checks`
+    /// feature. It does everything
+    /// [`Explode::check_invariants`](struct.Explode.html#method.check_invariants)
+    /// does, and also checks the output buffer.
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        self.parent.check_invariants();
+        assert!(self.pos <= self.buf.len(), "output position past end");
+    }
 }
 
-impl Explode {
-    /// Create a new Explode decompression state.
-    pub fn new() -> Self {
-        Explode {
-            state: ExplodeState::Start,
-            lit: None,
-            dict: None,
-            input: ExplodeInput {
-                next: ExplodeInputState::Waiting,
-                bitbuf: 0,
-                bitcount: 0,
-            },
-            window: ArrayDeque::new(),
+impl ExplodeConfig {
+    // fail if there is no room for more output under the limit
+    #[inline]
+    fn check_limit(&self, total_out: u64) -> Result<()> {
+        match self.output_limit {
+            Some(limit) if total_out >= limit => {
+                event!(WARN, limit, "output limit exceeded");
+                Err(Error::OutputLimitExceeded)
+            }
+            _ => Ok(()),
         }
     }
 
-    /// Provide a buffer to decompress into.
+    /// Create a new default configuration.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Decompress a stream without the usual two-byte header.
     ///
-    /// This returns a [`ExplodeBuffer`](struct.ExplodeBuffer.html)
-    /// handle that is used for feeding input to decompress and other
-    /// operations.
-    pub fn with_buffer<'a>(
-        &'a mut self,
-        buf: &'a mut [u8],
-    ) -> ExplodeBuffer<'a> {
-        ExplodeBuffer {
-            parent: self,
-            buf,
-            pos: 0,
-        }
+    /// The header values are provided here instead. `coded_literals`
+    /// is true if literals are Huffman coded, and `dict_size` is the
+    /// number of extra bits in distances, between 4 and 6 inclusive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dict_size` is not in `4..=6`.
+    pub fn headerless(mut self, coded_literals: bool, dict_size: u8) -> Self {
+        assert!((4..=6).contains(&dict_size), "dictionary size not in 4..=6");
+        self.header = Some((coded_literals as u8, dict_size));
+        self
     }
 
-    /// Returns true if decompression is finished.
+    /// Start decompressing `bit_offset` bits into the first byte.
     ///
-    /// If this function can't be used because a
-    /// [`ExplodeBuffer`](struct.ExplodeBuffer.html) is currently
-    /// borrowing this object mutably, you can use
-    /// [`ExplodeBuffer::done`](struct.ExplodeBuffer.html#method.done)
-    /// instead.
-    pub fn done(&self) -> bool {
-        if let ExplodeState::End = self.state {
-            true
-        } else {
-            false
-        }
+    /// This is useful when the compressed stream does not start on a
+    /// byte boundary, such as when it is embedded in some other
+    /// bit-packed data. Bits are used least significant first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit_offset` is not in `0..8`.
+    pub fn bit_offset(mut self, bit_offset: u8) -> Self {
+        assert!(bit_offset < 8, "bit offset not in 0..8");
+        self.bit_offset = bit_offset;
+        self
     }
-}
 
-/// Decompress a block of `data` in memory, using the given auxiliary
-/// buffer `buf`.
-///
-/// This gives you control over the size of the internal buffer
-/// used. If you do not need that control, use
-/// [`explode`](fn.explode.html) instead.
-///
-/// ```
-/// # fn main() -> explode::Result<()> {
-/// let mut buf: [u8; 1] = [0; 1];
-/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
-/// let result = explode::explode_with_buffer(&bytes, &mut buf)?;
-/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
-/// # Ok(()) }
-/// ```
-pub fn explode_with_buffer(data: &[u8], buf: &mut [u8]) -> Result<Vec<u8>> {
-    let mut dec = Explode::new();
-    let mut i = 0;
-    let mut out = Vec::with_capacity(buf.len());
-    loop {
-        let mut decbuf = dec.with_buffer(buf);
-        while i < data.len() {
-            match decbuf.feed(data[i]) {
-                Ok(()) => {
-                    let decompressed = decbuf.get();
-                    out.extend_from_slice(decompressed);
-                    if decbuf.done() {
-                        // we're done
-                        return Ok(out);
-                    }
-                    decbuf.reset();
-                }
+    /// Accept streams that end without an end code.
+    ///
+    /// Normally, a stream that runs out of input before the end code
+    /// is an error. With this set, running out of input between two
+    /// tokens is accepted instead, when signalled with
+    /// [`Explode::finish`](struct.Explode.html#method.finish). Use
+    /// [`Explode::ended_without_marker`](struct.Explode.html#method.ended_without_marker)
+    /// to tell when this happened.
+    ///
+    /// Running out of input in the middle of a token is still an
+    /// error. Unused bits in the last byte are treated as padding.
+    pub fn tolerant(mut self, tolerant: bool) -> Self {
+        self.tolerant = tolerant;
+        self
+    }
 
-                Err(Error::IncompleteInput) => {
-                    i += 1;
-                    continue;
-                }
+    /// Expect about `hint` bytes of decompressed output.
+    ///
+    /// Functions that collect all of the output into a `Vec`, such as
+    /// [`explode_with_config`](fn.explode_with_config.html), start
+    /// with room for this much output to avoid reallocating. This is
+    /// only a hint: output can be longer or shorter than `hint`.
+    pub fn capacity_hint(mut self, hint: usize) -> Self {
+        self.capacity_hint = hint;
+        self
+    }
 
-                Err(e) => return Err(e),
-            }
-        }
+    /// Stop with an error after `limit` bytes of output.
+    ///
+    /// Implode streams can expand enormously, so this protects
+    /// against untrusted input that decompresses to far more data
+    /// than expected. Producing more than `limit` bytes returns
+    /// [`Error::OutputLimitExceeded`](enum.Error.html#variant.OutputLimitExceeded).
+    /// Streams of exactly `limit` bytes decompress as usual. By
+    /// default there is no limit.
+    pub fn output_limit(mut self, limit: u64) -> Self {
+        self.output_limit = Some(limit);
+        self
+    }
 
-        // out of input
-        return Err(Error::IncompleteInput);
+    /// Start with `dictionary` already in the window.
+    ///
+    /// Some streams are compressed against known data, so that copies
+    /// at the start of the stream can refer back into it. The
+    /// dictionary is not part of the output. Only the last 4096
+    /// bytes are kept, as copies can never reach further back.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::ExplodeConfig;
+    ///
+    /// // copy 3 bytes from 3 back, then end
+    /// let bytes = vec![0x00, 0x04, 0x5f, 0x02, 0xfe, 0x01];
+    /// let config = ExplodeConfig::new().dictionary(b"HEYABC");
+    /// let result = explode::explode_with_config(&bytes, config)?;
+    /// assert_eq!(result, b"ABC");
+    /// # Ok(()) }
+    /// ```
+    pub fn dictionary(mut self, dictionary: &[u8]) -> Self {
+        let start = dictionary.len().saturating_sub(4096);
+        self.dictionary = dictionary[start..].to_vec();
+        self
     }
 }
 
-/// Decompress a block of `data` in memory.
-///
-/// ```
-/// # fn main() -> explode::Result<()> {
-/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
-/// let result = explode::explode(&bytes)?;
-/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
-/// # Ok(()) }
-/// ```
-///
-/// This function will internally decompress the given memory in
-/// blocks of 4096 bytes. If you wish to use a different block size,
-/// see [`explode_with_buffer`](fn.explode_with_buffer.html).
-pub fn explode(data: &[u8]) -> Result<Vec<u8>> {
-    let mut buf = [0; 4096];
-    explode_with_buffer(data, &mut buf)
+impl Default for Explode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Explode {
+    /// Create a new Explode decompression state.
+    pub fn new() -> Self {
+        Self::new_with(Default::default())
+    }
+
+    /// Create a new Explode decompression state, using the given
+    /// [`ExplodeConfig`](struct.ExplodeConfig.html).
+    pub fn new_with(config: ExplodeConfig) -> Self {
+        Explode::with_window_config([0; WINDOW_SIZE], config)
+    }
+
+    /// Continue decompressing from `inner` as an
+    /// [`ExplodeReader`](struct.ExplodeReader.html).
+    ///
+    /// This keeps all of the existing decompression state. See
+    /// [`ExplodeReader::with_explode`](struct.ExplodeReader.html#method.with_explode)
+    /// for details.
+    ///
+    /// This is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn into_reader<R: Read>(self, inner: R) -> ExplodeReader<R> {
+        ExplodeReader::with_explode(inner, self)
+    }
+
+    /// Returns an upper bound on the decompressed size of `data`.
+    ///
+    /// This is meant for preallocating output without trusting the
+    /// input. It only looks at the header and the length of `data`,
+    /// so it is fast, but loose: it is between about 172 and 188 times
+    /// the input length, depending on the dictionary size.
+    ///
+    /// The bound comes from the most output any one token can produce
+    /// per input bit. That is a copy of 518 bytes, using the shortest
+    /// length and distance codes, which takes 22 bits with a 1K
+    /// dictionary, or 24 bits with a 4K dictionary. Literals produce
+    /// far less per bit. It does not account for the end code, or for
+    /// copies that cannot come first, so streams of only long copies
+    /// come close to it, and typical streams use much less.
+    ///
+    /// If `data` does not start with a valid header, decompression
+    /// cannot produce any output and this returns 0.
+    ///
+    /// ```
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let bound = explode::Explode::decoded_len_upper_bound(&bytes);
+    /// assert!(bound >= 13);
+    /// ```
+    pub fn decoded_len_upper_bound(data: &[u8]) -> usize {
+        let dict = match data {
+            [lit, dict, ..] if *lit <= 1 && (4..=6).contains(dict) => *dict,
+            _ => return 0,
+        };
+
+        // the shortest distance code
+        let dist_code = (0..64)
+            .filter_map(|symbol| tables::DISTANCE.encode(symbol))
+            .map(|(_, len)| len)
+            .min()
+            .unwrap();
+
+        // find the copy with the most output per bit, as (len, bits)
+        let mut best = (1, 9);
+        for symbol in 0..16 {
+            let extra = tables::LEN_EXTRA[symbol];
+            let len = tables::LEN_BASE[symbol] + (1 << extra) - 1;
+            // 519 is the end code
+            let len = core::cmp::min(len, 518);
+            let (_, code) = tables::LENGTH.encode(symbol as u8).unwrap();
+            let dist_extra = if len == 2 { 2 } else { dict as usize };
+            let bits = 1 + code + extra as usize + dist_code + dist_extra;
+            if len * best.1 > best.0 * bits {
+                best = (len, bits);
+            }
+        }
+
+        let bits = (data.len() - 2) as u64 * 8;
+        (bits * best.0 as u64 / best.1 as u64) as usize
+    }
+}
+
+impl<S> Explode<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    /// Create a new Explode decompression state, keeping the window
+    /// in `window` instead of inside the decompressor.
+    ///
+    /// This lets many decompressors share window storage, for example
+    /// from a pool of buffers. `window` can be any type that can be
+    /// borrowed as a `&mut [u8]`, such as a mutable slice or a `Vec`.
+    /// Its contents are not used, and only the first 4096 bytes are
+    /// ever written to.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::Explode;
+    ///
+    /// let mut storage = vec![0; 4096];
+    /// let mut ex = Explode::with_window(&mut storage[..]);
+    /// let input = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut output = [0; 13];
+    /// ex.feed_slice_into(&input, &mut output)?;
+    /// assert_eq!(&output, b"AIAIAIAIAIAIA");
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is shorter than 4096 bytes.
+    pub fn with_window(window: S) -> Self {
+        Self::with_window_config(window, Default::default())
+    }
+
+    /// Create a new Explode decompression state with the given
+    /// [`ExplodeConfig`](struct.ExplodeConfig.html), keeping the
+    /// window in `window`.
+    ///
+    /// See [`with_window`](#method.with_window) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is shorter than 4096 bytes.
+    pub fn with_window_config(window: S, config: ExplodeConfig) -> Self {
+        let mut dec = Explode {
+            state: ExplodeState::Start,
+            length: tables::LENGTH.decoder(),
+            distance: tables::DISTANCE.decoder(),
+            literal: tables::LITERAL.decoder(),
+            lit: config.header.map(|(lit, _)| lit),
+            dict: config.header.map(|(_, dict)| dict),
+            input: ExplodeInput {
+                next: ExplodeInputState::Waiting,
+                bitbuf: 0,
+                bitcount: 0,
+                consumed: 0,
+            },
+            token_start: 0,
+            skip_bytes: 0,
+            skip_bits: config.bit_offset,
+            end_reason: None,
+            total_out: 0,
+            checkpoints: None,
+            transform: None,
+            window: Window::new(window),
+            config: Default::default(),
+        };
+        dec.fill_window(&config.dictionary);
+        dec.config = config;
+        dec
+    }
+
+    /// Consume this decompressor, and return the storage used for its
+    /// window.
+    pub fn into_window(self) -> S {
+        self.window.into_inner()
+    }
+
+    /// Copy this decompressor, so the copy continues from exactly the
+    /// same place.
+    ///
+    /// Returns `None` if a callback was set with
+    /// [`on_checkpoint`](#method.on_checkpoint) or
+    /// [`with_output_transform`](#method.with_output_transform), which
+    /// cannot be copied. Use
+    /// [`on_checkpoint_cloneable`](#method.on_checkpoint_cloneable) or
+    /// [`with_output_transform_cloneable`](#method.with_output_transform_cloneable)
+    /// for callbacks that can be.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::Explode;
+    ///
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut ex = Explode::new();
+    /// ex.feed_slice_into(&bytes[..4], &mut [0; 16])?;
+    ///
+    /// // try the rest on a copy, and keep the original if it fails
+    /// let mut attempt = ex.try_clone().unwrap();
+    /// attempt.feed_slice_into(&bytes[4..], &mut [0; 16])?;
+    /// assert!(attempt.done());
+    /// assert!(!ex.done());
+    /// # Ok(()) }
+    /// ```
+    pub fn try_clone(&self) -> Option<Self>
+    where
+        S: Clone,
+    {
+        let checkpoints = match self.checkpoints {
+            Some(ref hook) => Some(hook.try_clone()?),
+            None => None,
+        };
+        let transform = match self.transform {
+            Some((order, ref f)) => {
+                Some((order, OutputTransform(f.0.try_clone()?)))
+            }
+            None => None,
+        };
+        Some(Explode {
+            state: self.state.clone(),
+            length: self.length.clone(),
+            distance: self.distance.clone(),
+            literal: self.literal.clone(),
+            lit: self.lit,
+            dict: self.dict,
+            input: self.input.clone(),
+            token_start: self.token_start,
+            skip_bytes: self.skip_bytes,
+            skip_bits: self.skip_bits,
+            end_reason: self.end_reason,
+            total_out: self.total_out,
+            checkpoints,
+            transform,
+            config: self.config.clone(),
+            window: self.window.clone(),
+        })
+    }
+
+    /// Reset this decompressor, so it can be used on a new stream.
+    ///
+    /// This keeps the configuration this decompressor was created
+    /// with. It is equivalent to, but may be cheaper than, creating a
+    /// new decompressor with the same configuration.
+    pub fn reset(&mut self) {
+        let config = self.config.clone();
+        self.reset_with_config(config);
+    }
+
+    /// Reset this decompressor to use a new configuration, so it can
+    /// be used on a new stream.
+    pub fn reset_with_config(&mut self, config: ExplodeConfig) {
+        self.state = ExplodeState::Start;
+        self.lit = config.header.map(|(lit, _)| lit);
+        self.dict = config.header.map(|(_, dict)| dict);
+        self.input = ExplodeInput {
+            next: ExplodeInputState::Waiting,
+            bitbuf: 0,
+            bitcount: 0,
+            consumed: 0,
+        };
+        self.token_start = 0;
+        self.skip_bytes = 0;
+        self.skip_bits = config.bit_offset;
+        self.end_reason = None;
+        self.total_out = 0;
+        self.checkpoints = None;
+        self.transform = None;
+        self.window.clear();
+        self.fill_window(&config.dictionary);
+        self.config = config;
+    }
+
+    // add data to the window, keeping only as much as fits
+    fn fill_window(&mut self, data: &[u8]) {
+        let start = data.len().saturating_sub(self.window.capacity());
+        self.window.extend_from_slice(&data[start..]);
+    }
+
+    /// Call `f` with a [`Checkpoint`](struct.Checkpoint.html) about
+    /// every `interval` bytes of output.
+    ///
+    /// Checkpoints are only taken between tokens, so each one comes at
+    /// the first token boundary at or after a multiple of `interval`.
+    /// Use these with [`restore_state`](#method.restore_state) to start
+    /// decompressing from the middle of a large stream, without
+    /// decoding everything before it. See
+    /// [`Checkpoint`](struct.Checkpoint.html) for how much memory each
+    /// checkpoint uses.
+    ///
+    /// This replaces any earlier callback, and
+    /// [`reset`](#method.reset) removes it. An `Explode` with this
+    /// callback cannot be copied with
+    /// [`try_clone`](#method.try_clone); use
+    /// [`on_checkpoint_cloneable`](#method.on_checkpoint_cloneable)
+    /// for that.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::Explode;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let checkpoints = Arc::new(Mutex::new(vec![]));
+    /// let mut ex = Explode::new();
+    /// let saved = checkpoints.clone();
+    /// ex.on_checkpoint(2, move |c| saved.lock().unwrap().push(c));
+    /// ex.feed_slice_into(&bytes, &mut [0; 16])?;
+    ///
+    /// // restart one checkpoint in
+    /// let checkpoint = checkpoints.lock().unwrap()[0].clone();
+    /// let mut ex = Explode::new();
+    /// ex.restore_state(&checkpoint);
+    /// let start = (checkpoint.bit_position / 8) as usize;
+    /// let mut out = [0; 16];
+    /// let (_, len) = ex.feed_slice_into(&bytes[start..], &mut out)?;
+    /// assert_eq!(checkpoint.output_position, 2);
+    /// assert_eq!(&out[..len], b"AIAIAIAIAIA");
+    /// # Ok(()) }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    pub fn on_checkpoint<F>(&mut self, interval: usize, f: F)
+    where
+        F: FnMut(Checkpoint) + Send + 'static,
+    {
+        self.set_checkpoints(interval, Hook::Plain(Box::new(f)));
+    }
+
+    /// Call `f` with a [`Checkpoint`](struct.Checkpoint.html) about
+    /// every `interval` bytes of output, in a way that can be copied.
+    ///
+    /// This is the same as [`on_checkpoint`](#method.on_checkpoint),
+    /// but [`try_clone`](#method.try_clone) still works, and the copy
+    /// of the decompressor gets its own copy of `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    pub fn on_checkpoint_cloneable<F>(&mut self, interval: usize, f: F)
+    where
+        F: FnMut(Checkpoint) + Clone + Send + 'static,
+    {
+        self.set_checkpoints(interval, Hook::Cloneable(Box::new(f)));
+    }
+
+    fn set_checkpoints(&mut self, interval: usize, f: Hook<Checkpoint, ()>) {
+        assert!(interval > 0, "checkpoint interval is 0");
+        let interval = interval as u64;
+        self.checkpoints = Some(CheckpointHook {
+            interval,
+            next: (self.total_out / interval + 1) * interval,
+            f,
+        });
+    }
+
+    /// Apply `f` to every decompressed byte.
+    ///
+    /// `order` chooses whether `f` is applied before or after each
+    /// byte is added to the dictionary. Some archives mask the output
+    /// of implode, for example by XOR with a key, which needs
+    /// [`TransformOrder::AfterWindow`](enum.TransformOrder.html#variant.AfterWindow)
+    /// to undo.
+    ///
+    /// This replaces any earlier transform, and
+    /// [`reset`](#method.reset) removes it. An `Explode` with this
+    /// transform cannot be copied with
+    /// [`try_clone`](#method.try_clone); use
+    /// [`with_output_transform_cloneable`](#method.with_output_transform_cloneable)
+    /// for that.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::{Explode, TransformOrder};
+    ///
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut ex = Explode::new();
+    /// ex.with_output_transform(TransformOrder::AfterWindow, |b| b ^ 0x20);
+    /// let mut out = [0; 16];
+    /// let (_, len) = ex.feed_slice_into(&bytes, &mut out)?;
+    /// assert_eq!(&out[..len], b"aiaiaiaiaiaia");
+    /// # Ok(()) }
+    /// ```
+    pub fn with_output_transform<F>(&mut self, order: TransformOrder, f: F)
+    where
+        F: FnMut(u8) -> u8 + Send + 'static,
+    {
+        let f = OutputTransform(Hook::Plain(Box::new(f)));
+        self.transform = Some((order, f));
+    }
+
+    /// Apply `f` to every decompressed byte, in a way that can be
+    /// copied.
+    ///
+    /// This is the same as
+    /// [`with_output_transform`](#method.with_output_transform), but
+    /// [`try_clone`](#method.try_clone) still works, and the copy of
+    /// the decompressor gets its own copy of `f`.
+    pub fn with_output_transform_cloneable<F>(
+        &mut self,
+        order: TransformOrder,
+        f: F,
+    ) where
+        F: FnMut(u8) -> u8 + Clone + Send + 'static,
+    {
+        let f = OutputTransform(Hook::Cloneable(Box::new(f)));
+        self.transform = Some((order, f));
+    }
+
+    /// Continue decompressing from a
+    /// [`Checkpoint`](struct.Checkpoint.html).
+    ///
+    /// After this, input should start from byte
+    /// `checkpoint.bit_position / 8` of the compressed stream. Any
+    /// earlier state is lost, but the configuration, any callback
+    /// from [`on_checkpoint`](#method.on_checkpoint), and any
+    /// transform from
+    /// [`with_output_transform`](#method.with_output_transform) are
+    /// kept.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint header is invalid.
+    pub fn restore_state(&mut self, checkpoint: &Checkpoint) {
+        let header = checkpoint.header;
+        assert!(
+            (4..=6).contains(&header.dict_size),
+            "dictionary size not in 4..=6"
+        );
+
+        let hook = self.checkpoints.take();
+        let transform = self.transform.take();
+        self.reset();
+        self.transform = transform;
+        self.lit = Some(header.coded_literals as u8);
+        self.dict = Some(header.dict_size);
+        self.input.consumed = checkpoint.bit_position / 8;
+        self.token_start = self.input.consumed;
+        self.skip_bits = (checkpoint.bit_position % 8) as u8;
+        // the checkpoint already includes any preset dictionary
+        self.window.clear();
+        self.fill_window(&checkpoint.window);
+        self.total_out = checkpoint.output_position;
+
+        self.checkpoints = hook.map(|mut hook| {
+            hook.next = (self.total_out / hook.interval + 1) * hook.interval;
+            hook
+        });
+    }
+
+    // call the checkpoint callback, if one is due
+    fn checkpoint(&mut self) {
+        let due = match self.checkpoints {
+            Some(ref hook) => self.total_out >= hook.next,
+            None => false,
+        };
+        if !due {
+            return;
+        }
+
+        let window_size = match self.header() {
+            Some(header) => header.window_size(),
+            None => return,
+        };
+        let skip = self.window.len().saturating_sub(window_size);
+        let checkpoint = Checkpoint {
+            output_position: self.total_out,
+            bit_position: self.bit_position(),
+            header: self.header().unwrap(),
+            window: self.window.iter().skip(skip).cloned().collect(),
+        };
+        if let Some(ref mut hook) = self.checkpoints {
+            hook.next = (self.total_out / hook.interval + 1) * hook.interval;
+            hook.f.call(checkpoint);
+        }
+    }
+
+    /// Skip `n` bytes of input before the stream starts.
+    ///
+    /// This is useful for containers that put a signature or other
+    /// data right before the compressed stream. The skipped bytes
+    /// are counted by [`bit_position`](#method.bit_position), and any
+    /// [`ExplodeConfig::bit_offset`](struct.ExplodeConfig.html#method.bit_offset)
+    /// applies after them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any input has already been provided.
+    pub fn skip_prefix(&mut self, n: usize) {
+        let fresh = self.input.consumed == 0
+            && matches!(self.input.next, ExplodeInputState::Waiting);
+        assert!(fresh, "skip_prefix called after decoding started");
+        self.skip_bytes += n;
+    }
+
+    /// Decompress as much of `input` as possible into `output`.
+    ///
+    /// This returns the number of input bytes used, and the number of
+    /// output bytes produced. It stops when decompression is
+    /// finished, when `output` is full, or when all of `input` has
+    /// been used. Unlike [`ExplodeBuffer::feed`](struct.ExplodeBuffer.html#method.feed),
+    /// running out of input is not an error: call this again with
+    /// more input to continue.
+    ///
+    /// Input bytes that are used may be held internally until more
+    /// output space is available, so they should never be provided
+    /// twice.
+    pub fn feed_slice_into(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(usize, usize)> {
+        self.feed_slice_into_uninit(input, as_uninit(output))
+    }
+
+    // feed_slice_into, for output that may not be initialized yet.
+    // output[..len] is initialized afterwards
+    pub(crate) fn feed_slice_into_uninit(
+        &mut self,
+        input: &[u8],
+        output: &mut [MaybeUninit<u8>],
+    ) -> Result<(usize, usize)> {
+        let mut decbuf = ExplodeBuffer {
+            parent: self,
+            buf: output,
+            pos: 0,
+        };
+        let used = decbuf.feed_slice(input)?;
+        Ok((used, decbuf.len()))
+    }
+
+    /// Decompress exactly `out.len()` bytes from `input` into `out`.
+    ///
+    /// This is useful for containers that store the decompressed
+    /// size. Anything in the stream past `out.len()` bytes is
+    /// ignored, including whether it has an end code. If `input`
+    /// runs out, or the stream ends, before `out` is full, this
+    /// returns
+    /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput).
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut out = [0; 13];
+    /// explode::Explode::new().decode_exact_into(&bytes, &mut out)?;
+    /// assert_eq!(&out, b"AIAIAIAIAIAIA");
+    /// # Ok(()) }
+    /// ```
+    pub fn decode_exact_into(
+        &mut self,
+        input: &[u8],
+        out: &mut [u8],
+    ) -> Result<()> {
+        let (_, len) = self.feed_slice_into(input, out)?;
+        if len < out.len() {
+            event!(WARN, len, "input ended early");
+            Err(Error::IncompleteInput)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Provide a buffer to decompress into.
+    ///
+    /// This returns a [`ExplodeBuffer`](struct.ExplodeBuffer.html)
+    /// handle that is used for feeding input to decompress and other
+    /// operations.
+    pub fn with_buffer<'a>(
+        &'a mut self,
+        buf: &'a mut [u8],
+    ) -> ExplodeBuffer<'a, S> {
+        ExplodeBuffer {
+            parent: self,
+            buf: as_uninit(buf),
+            pos: 0,
+        }
+    }
+
+    /// Borrow this decompressor as a [`Read`][Read] that decompresses
+    /// from `inner`.
+    ///
+    ///  [Read]: https://doc.rust-lang.org/std/io/trait.Read.html
+    ///
+    /// Unlike [`into_reader`](#method.into_reader), this does not take
+    /// ownership, so decompression can continue in some other way
+    /// once the adapter is dropped. As with
+    /// [`ExplodeReader::with_explode`](struct.ExplodeReader.html#method.with_explode),
+    /// the decompressor must be ready for the next byte from `inner`
+    /// when the adapter is created, and will be again once the
+    /// adapter returns 0 or an error. After a read that fills the
+    /// output buffer, the decompressor may still hold part of the
+    /// last input byte.
+    ///
+    /// This is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn read_adapter<'a, R: Read>(
+        &'a mut self,
+        inner: &'a mut R,
+    ) -> impl Read + 'a {
+        ReadAdapter { dec: self, inner }
+    }
+
+    /// Decompress the rest of the stream from `input` into `out`.
+    ///
+    /// This keeps all of the existing decompression state, so it can
+    /// be used to finish a stream that was started some other way,
+    /// such as with a headerless configuration or by hand with
+    /// [`ExplodeBuffer::feed`](struct.ExplodeBuffer.html#method.feed).
+    /// As with
+    /// [`ExplodeReader::with_explode`](struct.ExplodeReader.html#method.with_explode),
+    /// the decompressor must be ready for the next byte from `input`.
+    ///
+    /// Returns the number of bytes written to `out`. If `out` stops
+    /// accepting data, this returns an
+    /// [`Error::IO`](enum.Error.html#variant.IO) with kind
+    /// [`WriteZero`][WriteZero], like [`Write::write_all`][write_all].
+    ///
+    ///  [WriteZero]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.WriteZero
+    ///  [write_all]: https://doc.rust-lang.org/std/io/trait.Write.html#method.write_all
+    ///
+    /// This is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn copy_to<R: Read, W: Write>(
+        &mut self,
+        input: R,
+        out: &mut W,
+    ) -> Result<u64> {
+        self.copy_to_with_checksum(input, out, &mut NoChecksum)
+    }
+
+    /// Decompress the rest of the stream from `input` into `out`, and
+    /// add everything written to `checksum`.
+    ///
+    /// This is otherwise the same as [`copy_to`](#method.copy_to).
+    ///
+    /// This is only available with the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn copy_to_with_checksum<R: Read, W: Write, C: Checksum>(
+        &mut self,
+        mut input: R,
+        out: &mut W,
+        checksum: &mut C,
+    ) -> Result<u64> {
+        let mut inbuf = [0; 4096];
+        let mut outbuf = [0; 4096];
+        let mut start = 0;
+        let mut end = 0;
+        let mut eof = false;
+        let mut total = 0;
+        while !self.done() {
+            if start >= end && !eof {
+                end = match input.read(&mut inbuf) {
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    result => result?,
+                };
+                start = 0;
+                eof = end == 0;
+            }
+
+            let (used, len) =
+                self.feed_slice_into(&inbuf[start..end], &mut outbuf)?;
+            start += used;
+            out.write_all(&outbuf[..len])?;
+            checksum.update(&outbuf[..len]);
+            total += len as u64;
+
+            if eof && len < outbuf.len() {
+                // out of input
+                self.finish()?;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Decompress all of `input`, discarding the output.
+    ///
+    /// This is useful when only the final state of the decompressor
+    /// is needed, and not the output itself. Errors are still
+    /// reported as usual, and if `input` runs out before the end of
+    /// the compressed stream, this returns
+    /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput).
+    pub fn run_discarding(&mut self, input: &[u8]) -> Result<()> {
+        if self.done() {
+            return Ok(());
+        }
+
+        let mut buf = [0; 4096];
+        let mut decbuf = self.with_buffer(&mut buf);
+        let mut i = 0;
+        while i < input.len() {
+            match decbuf.feed(input[i]) {
+                Ok(()) => {
+                    if decbuf.done() {
+                        return Ok(());
+                    }
+                    decbuf.reset();
+                }
+
+                Err(Error::IncompleteInput) => i += 1,
+
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.finish()
+    }
+
+    /// Iterate over the decompressed bytes of `input`.
+    ///
+    /// Bytes are decompressed a block at a time, as they are needed.
+    /// The iterator ends at the end of the stream. Errors, including
+    /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput)
+    /// if `input` runs out first, are returned as one `Err` item,
+    /// and then the iterator ends.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::Explode;
+    ///
+    /// let input = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut ex = Explode::new();
+    /// let first: Vec<u8> = ex.bytes(&input).take(4).collect::<Result<_, _>>()?;
+    /// assert_eq!(first, b"AIAI");
+    /// # Ok(()) }
+    /// ```
+    pub fn bytes<'a>(
+        &'a mut self,
+        input: &'a [u8],
+    ) -> impl Iterator<Item = Result<u8>> + 'a {
+        let mut buf = vec![0; 4096];
+        let mut i = 0;
+        let mut pos = 0;
+        let mut len = 0;
+        let mut failed = false;
+        core::iter::from_fn(move || {
+            while pos >= len {
+                if failed || self.done() {
+                    return None;
+                }
+                let result = self
+                    .feed_slice_into(&input[i..], &mut buf)
+                    .and_then(|(used, n)| {
+                        i += used;
+                        if n == 0 && !self.done() {
+                            // out of input
+                            self.finish()?;
+                        }
+                        Ok(n)
+                    });
+                match result {
+                    Ok(n) => {
+                        pos = 0;
+                        len = n;
+                    }
+                    Err(e) => {
+                        failed = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            pos += 1;
+            Some(Ok(buf[pos - 1]))
+        })
+    }
+
+    /// Signal that there is no more input.
+    ///
+    /// This returns `Ok(())` if decompression is finished, and
+    /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput)
+    /// otherwise. If this decompressor was configured with
+    /// [`ExplodeConfig::tolerant`](struct.ExplodeConfig.html#method.tolerant),
+    /// a stream that ends cleanly between two tokens is also accepted,
+    /// and decompression is marked as finished.
+    ///
+    /// This should only be called after
+    /// [`ExplodeBuffer::feed`](struct.ExplodeBuffer.html#method.feed)
+    /// has asked for more input.
+    pub fn finish(&mut self) -> Result<()> {
+        use ExplodeState::*;
+        if self.done() {
+            return Ok(());
+        }
+
+        let waiting = matches!(self.input.next, ExplodeInputState::Waiting);
+        let header = self.lit.is_some() && self.dict.is_some();
+        let clean = match self.state {
+            Start => true,
+            // the rest of the current byte may be padding
+            Copy { .. } | End => false,
+            _ => self.token_start == self.input.consumed,
+        };
+
+        if self.config.tolerant && waiting && header && clean {
+            event!(DEBUG, "ended without end code");
+            self.state = End;
+            self.end_reason = Some(EndReason::MissingEndCode);
+            Ok(())
+        } else {
+            event!(WARN, "input ended early");
+            Err(Error::IncompleteInput)
+        }
+    }
+
+    /// Returns the number of input bits used so far.
+    ///
+    /// This counts from the start of the first input byte, including
+    /// any bits skipped with
+    /// [`ExplodeConfig::bit_offset`](struct.ExplodeConfig.html#method.bit_offset).
+    /// Once decompression is [`done`](#method.done), this is the
+    /// position of the first bit after the end code.
+    ///
+    /// The end code rarely ends on a byte boundary. The remaining bits
+    /// of the last byte are not used, so any data following the
+    /// stream at the bit level starts at bit `bit_position() % 8` of
+    /// input byte `bit_position() / 8`. If the stream is instead
+    /// followed by byte-aligned data, it starts at byte
+    /// `bit_position() / 8`, rounded up.
+    pub fn bit_position(&self) -> u64 {
+        self.input.consumed * 8 - self.input.bitcount as u64
+    }
+
+    /// Returns the number of bytes of output produced so far.
+    ///
+    /// This counts across every
+    /// [`ExplodeBuffer`](struct.ExplodeBuffer.html) used with this
+    /// decompressor, and is only set back to 0 by
+    /// [`reset`](#method.reset). A preset
+    /// [`dictionary`](struct.ExplodeConfig.html#method.dictionary) is
+    /// not counted.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut ex = explode::Explode::new();
+    /// let mut out = [0; 4];
+    /// let (used, _) = ex.feed_slice_into(&bytes, &mut out)?;
+    /// ex.feed_slice_into(&bytes[used..], &mut out)?;
+    /// assert_eq!(ex.total_out(), 8);
+    /// # Ok(()) }
+    /// ```
+    pub fn total_out(&self) -> u64 {
+        self.total_out
+    }
+
+    /// Returns the stream header, once it has been parsed.
+    ///
+    /// For headerless streams, this is the header provided by
+    /// [`ExplodeConfig::headerless`](struct.ExplodeConfig.html#method.headerless).
+    pub fn header(&self) -> Option<Header> {
+        match (self.lit, self.dict) {
+            (Some(lit), Some(dict)) => Some(Header {
+                coded_literals: lit > 0,
+                dict_size: dict,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns how many more input bytes are needed to parse the
+    /// header.
+    ///
+    /// This is 0 once the header is parsed, or for headerless
+    /// streams. Otherwise, it counts the bytes not yet provided,
+    /// including any skipped with
+    /// [`skip_prefix`](#method.skip_prefix) or
+    /// [`ExplodeConfig::bit_offset`](struct.ExplodeConfig.html#method.bit_offset).
+    pub fn header_bytes_needed(&self) -> usize {
+        if self.header().is_some() {
+            return 0;
+        }
+
+        let mut bits = self.skip_bytes * 8 + self.skip_bits as usize;
+        if self.lit.is_none() {
+            bits += 8;
+        }
+        // the dictionary size is always still needed here
+        bits += 8;
+
+        let mut have = self.input.bitcount as usize;
+        if let ExplodeInputState::Available(_) = self.input.next {
+            have += 8;
+        }
+        bits.saturating_sub(have).div_ceil(8)
+    }
+
+    /// Returns true if decompression finished without an end code.
+    ///
+    /// This can only happen if the decompressor was configured with
+    /// [`ExplodeConfig::tolerant`](struct.ExplodeConfig.html#method.tolerant).
+    pub fn ended_without_marker(&self) -> bool {
+        self.end_reason == Some(EndReason::MissingEndCode)
+    }
+
+    /// Returns why decompression stopped.
+    ///
+    /// This is `None` until decompression is [`done`](#method.done).
+    pub fn end_reason(&self) -> Option<EndReason> {
+        self.end_reason
+    }
+
+    /// Returns the window of recent output that copies can refer
+    /// back to, oldest first, as two slices to be read one after the
+    /// other.
+    ///
+    /// This holds up to 4096 bytes of the most recent output,
+    /// including any
+    /// [`ExplodeConfig::dictionary`](struct.ExplodeConfig.html#method.dictionary),
+    /// even if the dictionary size in the header is smaller. After
+    /// [`run_discarding`](#method.run_discarding), this is the end of
+    /// the decompressed stream. Nothing is copied or allocated.
+    ///
+    /// This is also useful to see what a copy had available when
+    /// decompression fails with
+    /// [`Error::BadDistance`](enum.Error.html#variant.BadDistance).
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::Explode;
+    ///
+    /// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut ex = Explode::new();
+    /// ex.run_discarding(&bytes)?;
+    /// let (a, b) = ex.window();
+    /// assert_eq!([a, b].concat(), b"AIAIAIAIAIAIA");
+    /// # Ok(()) }
+    /// ```
+    pub fn window(&self) -> (&[u8], &[u8]) {
+        self.window.as_slices()
+    }
+
+    /// Returns what the decompressor is doing, or waiting for input
+    /// to continue.
+    pub fn phase(&self) -> Phase {
+        use ExplodeState::*;
+        match self.state {
+            _ if self.header().is_none() => Phase::Header,
+            Start | Literal | LiteralCoded => Phase::Literal,
+            Length | LengthExtra { .. } => Phase::Length,
+            Distance { .. } | DistanceExtra { .. } => Phase::Distance,
+            Copy { .. } => Phase::Copy,
+            End => Phase::Done,
+        }
+    }
+
+    /// Returns why decompression last stopped.
+    ///
+    /// This tells apart a stream that is finished from one that is
+    /// waiting for input, or for room in the output buffer, without
+    /// looking at the errors returned while feeding it.
+    ///
+    /// ```
+    /// # fn main() -> explode::Result<()> {
+    /// use explode::{Explode, Status};
+    ///
+    /// let input = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let mut ex = Explode::new();
+    /// let mut output = [0; 8];
+    /// ex.feed_slice_into(&input[..4], &mut output)?;
+    /// assert_eq!(ex.status(), Status::NeedsInput);
+    /// let (used, _) = ex.feed_slice_into(&input[4..], &mut output)?;
+    /// assert_eq!(ex.status(), Status::InProgress);
+    /// ex.feed_slice_into(&input[4 + used..], &mut output)?;
+    /// assert_eq!(ex.status(), Status::Finished);
+    /// # Ok(()) }
+    /// ```
+    pub fn status(&self) -> Status {
+        if self.done() {
+            Status::Finished
+        } else if let ExplodeInputState::Waiting = self.input.next {
+            Status::NeedsInput
+        } else {
+            Status::InProgress
+        }
+    }
+
+    /// Returns true if decompression is finished.
+    ///
+    /// If this function can't be used because a
+    /// [`ExplodeBuffer`](struct.ExplodeBuffer.html) is currently
+    /// borrowing this object mutably, you can use
+    /// [`ExplodeBuffer::done`](struct.ExplodeBuffer.html#method.done)
+    /// instead.
+    pub fn done(&self) -> bool {
+        matches!(self.state, ExplodeState::End)
+    }
+
+    /// Panic if the internal state is inconsistent.
+    ///
+    /// This is only available in tests, or with the `debug-checks`
+    /// feature. It is intended to be called between steps of
+    /// decompression in tests and fuzz targets, to catch bugs in the
+    /// decompressor as early as possible.
+    #[cfg(any(test, feature = "debug-checks"))]
+    pub fn check_invariants(&self) {
+        use ExplodeState::*;
+
+        // only unused bits are kept in the bit buffer
+        assert!(self.input.bitcount < 8, "too many bits buffered");
+        assert_eq!(self.input.bitbuf >> self.input.bitcount, 0);
+        assert!(self.token_start <= self.input.consumed);
+        assert!(self.window.len() <= 4096);
+
+        // header is parsed before anything else happens
+        if let Some(lit) = self.lit {
+            assert!(lit <= 1, "bad literal flag");
+        }
+        if let Some(dict) = self.dict {
+            assert!((4..=6).contains(&dict), "bad dictionary size");
+        }
+        if self.lit.is_none() || self.dict.is_none() {
+            assert!(matches!(self.state, Start), "decoding before header");
+        }
+        assert_eq!(self.done(), self.end_reason.is_some());
+        if self.ended_without_marker() {
+            assert!(self.config.tolerant);
+        }
+
+        match self.state {
+            LengthExtra { symbol } => assert!(symbol < 16),
+            Distance { len } => assert!((2..=518).contains(&len)),
+            DistanceExtra { len, symbol } => {
+                assert!((2..=518).contains(&len));
+                assert!(symbol < 64);
+            }
+            Copy { idx, len } => {
+                assert!(len <= 518);
+                assert!(len == 0 || idx < self.window.len());
+            }
+            LiteralCoded => assert_eq!(self.lit, Some(1)),
+            Literal => assert_eq!(self.lit, Some(0)),
+            _ => (),
+        }
+    }
+}
+
+/// Decompress a block of `data` in memory, using the given auxiliary
+/// buffer `buf`.
+///
+/// This gives you control over the size of the internal buffer
+/// used. If you do not need that control, use
+/// [`explode`](fn.explode.html) instead.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let mut buf: [u8; 1] = [0; 1];
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode_with_buffer(&bytes, &mut buf)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+pub fn explode_with_buffer(data: &[u8], buf: &mut [u8]) -> Result<Vec<u8>> {
+    span!("explode", len = data.len());
+
+    let mut dec = Explode::new();
+    explode_all(&mut dec, data, buf)
+}
+
+// decompress all of data with dec, using buf to hold output
+fn explode_all<S: AsRef<[u8]> + AsMut<[u8]>>(
+    dec: &mut Explode<S>,
+    data: &[u8],
+    buf: &mut [u8],
+) -> Result<Vec<u8>> {
+    let capacity = core::cmp::max(buf.len(), dec.config.capacity_hint);
+    let mut out = Vec::with_capacity(capacity);
+    explode_chunks(dec, data, buf, |chunk| {
+        out.extend_from_slice(chunk);
+        Ok(())
+    })?;
+    Ok(out)
+}
+
+// decompress all of data with dec, passing each filled chunk of buf to f
+fn explode_chunks<S, F>(
+    dec: &mut Explode<S>,
+    data: &[u8],
+    buf: &mut [u8],
+    mut f: F,
+) -> Result<()>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+    F: FnMut(&[u8]) -> Result<()>,
+{
+    let mut i = 0;
+    while !dec.done() {
+        let (used, len) = dec.feed_slice_into(&data[i..], buf)?;
+        f(&buf[..len])?;
+        i += used;
+        if i >= data.len() && len < buf.len() {
+            // out of input
+            dec.finish()?;
+        }
+    }
+    Ok(())
+}
+
+/// Decompress a block of `data` in memory.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode(&bytes)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+///
+/// This function will internally decompress the given memory in
+/// blocks of 4096 bytes. If you wish to use a different block size,
+/// see [`explode_with_buffer`](fn.explode_with_buffer.html).
+pub fn explode(data: &[u8]) -> Result<Vec<u8>> {
+    let mut buf = [0; 4096];
+    explode_with_buffer(data, &mut buf)
+}
+
+/// Decompress a stream that starts `offset` bytes into `data`.
+///
+/// This is useful for streams embedded in a container after a header
+/// of known size. Anything after the stream is ignored, and the
+/// number of bytes it used can be found with
+/// [`explode_counted`](fn.explode_counted.html). If `offset` is past
+/// the end of `data`, this returns
+/// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput).
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0xca, 0xfe, 0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode_at(&bytes, 2)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+pub fn explode_at(data: &[u8], offset: usize) -> Result<Vec<u8>> {
+    match data.get(offset..) {
+        Some(rest) => explode(rest),
+        None => {
+            event!(WARN, offset, "offset past end of input");
+            Err(Error::IncompleteInput)
+        }
+    }
+}
+
+/// Decompress a block of `data` in memory, with the given
+/// configuration.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// use explode::ExplodeConfig;
+///
+/// let bytes = vec![0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let config = ExplodeConfig::new().headerless(false, 4).capacity_hint(13);
+/// let result = explode::explode_with_config(&bytes, config)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+///
+/// Like [`explode`](fn.explode.html), this decompresses in blocks of
+/// 4096 bytes internally.
+///
+/// With [`ExplodeConfig::tolerant`](struct.ExplodeConfig.html#method.tolerant),
+/// a stream that stops between tokens without an end code is also
+/// returned without an error. To tell that apart from a stream with
+/// an end code, decompress with [`Explode`](struct.Explode.html) and
+/// check [`Explode::end_reason`](struct.Explode.html#method.end_reason).
+pub fn explode_with_config(
+    data: &[u8],
+    config: ExplodeConfig,
+) -> Result<Vec<u8>> {
+    span!("explode_with_config", len = data.len());
+    let mut dec = Explode::new_with(config);
+    let mut buf = [0; 4096];
+    explode_all(&mut dec, data, &mut buf)
+}
+
+/// Decompress a block of `data` in memory, writing the result to
+/// `out`.
+///
+/// This decompresses in blocks of 4096 bytes, and writes each block
+/// to `out` as it is produced, so the whole result is never held in
+/// memory. Returns the number of bytes written. If decompression
+/// fails partway through, some output may already have been written.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let mut result = vec![];
+/// let len = explode::explode_to_writer(&bytes, &mut result)?;
+/// assert_eq!(len, 13);
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+///
+/// This is only available with the `std` feature.
+#[cfg(feature = "std")]
+pub fn explode_to_writer<W: Write>(data: &[u8], out: &mut W) -> Result<u64> {
+    span!("explode_to_writer", len = data.len());
+    let mut dec = Explode::new();
+    let mut buf = [0; 4096];
+    let mut total = 0;
+    explode_chunks(&mut dec, data, &mut buf, |chunk| {
+        out.write_all(chunk)?;
+        total += chunk.len() as u64;
+        Ok(())
+    })?;
+    Ok(total)
+}
+
+/// The result of [`explode_precise`](fn.explode_precise.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeReport {
+    /// The decompressed data.
+    pub output: Vec<u8>,
+    /// How many bytes of input contain any bits of the stream,
+    /// including the header and the end code. Any byte-aligned data
+    /// following the stream starts at this index.
+    pub input_bytes_consumed: usize,
+    /// How many bits of the last consumed byte are used, counting
+    /// from the least significant bit, or 0 if the stream ends exactly
+    /// on a byte boundary. Any bit-packed data following the stream
+    /// starts at this bit of byte `input_bytes_consumed - 1`, unless
+    /// this is 0.
+    pub end_bit_offset: u8,
+}
+
+/// Decompress a block of `data` in memory, and report exactly where
+/// the stream ended.
+///
+/// This is useful for containers that put other data right after a
+/// compressed stream. See [`DecodeReport`](struct.DecodeReport.html)
+/// for what is reported.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f, 0x42];
+/// let report = explode::explode_precise(&bytes)?;
+/// assert_eq!(report.output, "AIAIAIAIAIAIA".as_bytes());
+/// assert_eq!(report.input_bytes_consumed, 8);
+/// assert_eq!(report.end_bit_offset, 7);
+/// # Ok(()) }
+/// ```
+pub fn explode_precise(data: &[u8]) -> Result<DecodeReport> {
+    span!("explode_precise", len = data.len());
+    let mut dec = Explode::new();
+    let mut buf = [0; 4096];
+    let output = explode_all(&mut dec, data, &mut buf)?;
+    let pos = dec.bit_position();
+    Ok(DecodeReport {
+        output,
+        input_bytes_consumed: pos.div_ceil(8) as usize,
+        end_bit_offset: (pos % 8) as u8,
+    })
+}
+
+/// Decompress a block of `data` in memory, into `out`.
+///
+/// Returns the length of the decompressed data, which is at the
+/// start of `out`. Unlike [`explode_with_buffer`](fn.explode_with_buffer.html),
+/// this never allocates, so it is useful without the heap. If the
+/// output does not fit in `out`, this returns
+/// [`Error::OutputFull`](enum.Error.html#variant.OutputFull).
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let mut out = [0; 16];
+/// let len = explode::explode_into(&bytes, &mut out)?;
+/// assert_eq!(&out[..len], "AIAIAIAIAIAIA".as_bytes());
+///
+/// let mut small = [0; 12];
+/// let err = explode::explode_into(&bytes, &mut small);
+/// assert_eq!(err, Err(explode::Error::OutputFull));
+/// # Ok(()) }
+/// ```
+pub fn explode_into(data: &[u8], out: &mut [u8]) -> Result<usize> {
+    span!("explode_into", len = data.len());
+    let mut dec = Explode::new();
+    let (used, len) = dec.feed_slice_into(data, out)?;
+    if let Status::InProgress = dec.status() {
+        // out is full, but the stream may end right here
+        dec.feed_slice_into(&data[used..], &mut [])?;
+    }
+    match dec.status() {
+        Status::Finished => Ok(len),
+        Status::NeedsInput => dec.finish().map(|()| len),
+        Status::InProgress => {
+            event!(WARN, len, "output full");
+            Err(Error::OutputFull)
+        }
+    }
+}
+
+/// Decompress a block of `data` in memory, and return the result
+/// with the number of input bytes used.
+///
+/// The count includes the header and the byte holding the end of the
+/// end code, so any data following the stream starts at that index.
+/// For the bit position within the last byte, use
+/// [`explode_precise`](fn.explode_precise.html).
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f, 0x42];
+/// let (result, used) = explode::explode_counted(&bytes)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// assert_eq!(&bytes[used..], &[0x42]);
+/// # Ok(()) }
+/// ```
+pub fn explode_counted(data: &[u8]) -> Result<(Vec<u8>, usize)> {
+    let report = explode_precise(data)?;
+    Ok((report.output, report.input_bytes_consumed))
+}
+
+/// Find the decompressed length of `data`, without keeping the
+/// output.
+///
+/// This decompresses the whole stream, but only counts the output,
+/// so it needs no more memory than a small buffer. It is useful to
+/// size an output buffer exactly, or to reject streams that are too
+/// large before decompressing them for real. For a quick bound that
+/// does not decompress anything, use
+/// [`Explode::decoded_len_upper_bound`](struct.Explode.html#method.decoded_len_upper_bound).
+/// Errors are the same as for [`explode`](fn.explode.html).
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// assert_eq!(explode::decompressed_len(&bytes)?, 13);
+/// # Ok(()) }
+/// ```
+pub fn decompressed_len(data: &[u8]) -> Result<u64> {
+    span!("decompressed_len", len = data.len());
+    let mut dec = Explode::new();
+    dec.run_discarding(data)?;
+    Ok(dec.total_out())
+}
+
+/// One stream decompressed by [`members`](fn.members.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    /// The stream header.
+    pub header: Header,
+    /// The decompressed data.
+    pub output: Vec<u8>,
+    /// Where the stream was in the input, from the first header byte
+    /// up to the byte containing the end code.
+    pub input_range: core::ops::Range<usize>,
+}
+
+/// Decompress each of several streams stored one after another in
+/// `data`.
+///
+/// Each stream starts on the byte after the previous one ends.
+/// Iteration stops at the end of `data`, or when the remaining
+/// bytes do not start with a valid header, so trailing padding is
+/// ignored. A stream with a valid header that fails to decompress
+/// produces an error, and then iteration stops.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let mut data = bytes.clone();
+/// data.extend_from_slice(&bytes);
+/// data.extend_from_slice(&[0, 0, 0]);
+///
+/// let members = explode::members(&data).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(members.len(), 2);
+/// assert_eq!(members[1].output, "AIAIAIAIAIAIA".as_bytes());
+/// assert_eq!(members[1].input_range, 8..16);
+/// # Ok(()) }
+/// ```
+pub fn members(data: &[u8]) -> impl Iterator<Item = Result<Member>> + '_ {
+    let mut start = 0;
+    let mut failed = false;
+    let mut buf = vec![0; 4096];
+    core::iter::from_fn(move || {
+        let rest = &data[start..];
+        let valid = match rest {
+            [lit, dict, ..] => *lit <= 1 && (4..=6).contains(dict),
+            _ => false,
+        };
+        if failed || !valid {
+            return None;
+        }
+
+        let mut dec = Explode::new();
+        let result = explode_all(&mut dec, rest, &mut buf).map(|output| {
+            let len = dec.bit_position().div_ceil(8) as usize;
+            let member = Member {
+                header: dec.header().unwrap(),
+                output,
+                input_range: start..start + len,
+            };
+            start += len;
+            member
+        });
+        failed = result.is_err();
+        Some(result)
+    })
+}
+
+/// Decompress a block of `data` in memory, into a
+/// [`Bytes`](https://docs.rs/bytes/1/bytes/struct.Bytes.html).
+///
+/// This is only available with the `bytes` feature. The
+/// decompressed data is not copied into the result.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode_to_bytes(&bytes)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "bytes")]
+pub fn explode_to_bytes(data: &[u8]) -> Result<bytes::Bytes> {
+    Ok(explode(data)?.into())
+}
+
+/// Decompress a stream from `src` into `dst`, using the
+/// [`Buf`](https://docs.rs/bytes/1/bytes/trait.Buf.html) and
+/// [`BufMut`](https://docs.rs/bytes/1/bytes/trait.BufMut.html)
+/// traits.
+///
+/// Input is read straight from the chunks of `src`, which can split
+/// the stream anywhere. `src` is advanced past the end of the stream,
+/// and any data after it is left there. Output is written to `dst`
+/// in blocks, through a small buffer on the stack.
+///
+/// If `src` runs out before the end of the stream, this returns
+/// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput).
+/// If `dst` runs out of room, this returns
+/// [`Error::OutputFull`](enum.Error.html#variant.OutputFull).
+///
+/// This is only available with the `bytes` feature.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// use bytes::Buf;
+///
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// // input in two pieces
+/// let mut src = (&bytes[..3]).chain(&bytes[3..]);
+/// let mut dst = bytes::BytesMut::new();
+/// explode::explode_buf(&mut src, &mut dst)?;
+/// assert_eq!(dst, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "bytes")]
+pub fn explode_buf<B, M>(src: &mut B, dst: &mut M) -> Result<()>
+where
+    B: bytes::Buf,
+    M: bytes::BufMut,
+{
+    span!("explode_buf", len = src.remaining());
+    let mut dec = Explode::new();
+    let mut buf = [0; 4096];
+    loop {
+        let room = buf.len().min(dst.remaining_mut());
+        let (used, len) =
+            dec.feed_slice_into(src.chunk(), &mut buf[..room])?;
+        src.advance(used);
+        dst.put_slice(&buf[..len]);
+        match dec.status() {
+            Status::Finished => return Ok(()),
+            Status::NeedsInput if !src.has_remaining() => {
+                return dec.finish()
+            }
+            Status::InProgress if !dst.has_remaining_mut() => {
+                event!(WARN, "output full");
+                return Err(Error::OutputFull);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Decompress a block of `data` in memory, reusing per-thread
+/// decompressor state.
+///
+/// This behaves exactly like [`explode`](fn.explode.html), but keeps
+/// one [`Explode`](struct.Explode.html) per thread to decompress
+/// with, instead of creating a new one on every call. This is useful
+/// when decompressing many small blocks, possibly in parallel.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode_pooled(&bytes)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+///
+/// This is only available with the `std` feature.
+#[cfg(feature = "std")]
+pub fn explode_pooled(data: &[u8]) -> Result<Vec<u8>> {
+    span!("explode_pooled", len = data.len());
+    thread_local! {
+        static POOL: std::cell::RefCell<Explode> =
+            std::cell::RefCell::new(Explode::new());
+    }
+
+    let mut buf = [0; 4096];
+    POOL.with(|dec| {
+        let mut dec = dec.borrow_mut();
+        dec.reset();
+        explode_all(&mut dec, data, &mut buf)
+    })
+}
+
+/// Decompress a block of `data` in memory, from any type that can be
+/// viewed as a byte slice.
+///
+/// This is a thin wrapper around [`explode`](fn.explode.html) that
+/// accepts `Vec<u8>`, `Box<[u8]>`, and similar types directly.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode_any(bytes)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+pub fn explode_any<D: AsRef<[u8]>>(data: D) -> Result<Vec<u8>> {
+    explode(data.as_ref())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{explode, explode_with_buffer, Error};
-    use crate::examples::EXAMPLES;
+/// Decompress a block of `data` in memory, and transcode it to a
+/// `String` using `map`.
+///
+/// Each decompressed byte `b` becomes the character `map[b]`. This is
+/// useful for text stored in a legacy code page, such as CP437.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// let mut map = ['?'; 256];
+/// for (i, c) in map.iter_mut().enumerate().take(128) {
+///     *c = i as u8 as char;
+/// }
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let result = explode::explode_transcode(&bytes, &map)?;
+/// assert_eq!(result, "AIAIAIAIAIAIA");
+/// # Ok(()) }
+/// ```
+pub fn explode_transcode(data: &[u8], map: &[char; 256]) -> Result<String> {
+    let decompressed = explode(data)?;
+    Ok(decompressed.iter().map(|b| map[*b as usize]).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decompressed_len, explode, explode_all, explode_any, explode_at,
+        explode_chunks, explode_counted, explode_into, explode_precise,
+        explode_transcode, explode_with_buffer, explode_with_config, members,
+        Checkpoint, EndReason, Error, Explode, ExplodeConfig, Header, Phase,
+        Status, TransformOrder,
+    };
+    #[cfg(feature = "std")]
+    use super::{explode_pooled, explode_to_writer};
+    use crate::examples::{uncoded, BitWriter, EXAMPLES};
+    use crate::tables;
+    use alloc::{vec, vec::Vec};
+
+    #[test]
+    fn explode_simple() {
+        for (encoded, decoded) in EXAMPLES {
+            let ours = explode(encoded).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    fn explode_dictionary() {
+        let dictionary = b"some shared context";
+
+        // the first token is a copy into the dictionary
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        out.copy(6, dictionary.len(), 4);
+        out.literal(b'!');
+        out.copy(3, 3, 4);
+        out.end();
+        let encoded = out.finish();
+
+        let config = ExplodeConfig::new().dictionary(dictionary);
+        let ours = explode_with_config(&encoded, config.clone()).unwrap();
+        assert_eq!(&ours[..], b"some s! s!");
+        assert!(explode(&encoded).is_err());
+
+        // reset keeps the dictionary
+        let mut dec = Explode::new_with(config);
+        let mut buf = [0; 16];
+        explode_all(&mut dec, &encoded, &mut buf).unwrap();
+        dec.reset();
+        let ours = explode_all(&mut dec, &encoded, &mut buf).unwrap();
+        assert_eq!(&ours[..], b"some s! s!");
+
+        // only the last 4096 bytes are kept
+        let mut long = vec![0; 5000];
+        long.extend_from_slice(dictionary);
+        let config = ExplodeConfig::new().dictionary(&long);
+        let dec = Explode::new_with(config);
+        assert_eq!(dec.window.len(), 4096);
+        let kept = &long[long.len() - 4096..];
+        assert!(dec.window.iter().copied().eq(kept.iter().copied()));
+    }
+
+    #[test]
+    fn explode_total_out() {
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 7, 4096].iter() {
+                let mut dec = Explode::new();
+                let mut buf = vec![0; *size];
+                let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+                assert_eq!(dec.total_out(), decoded.len() as u64);
+                assert_eq!(ours.len(), decoded.len());
+                dec.reset();
+                assert_eq!(dec.total_out(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_window() {
+        let mut buf = [0; 1000];
+        for (encoded, _) in EXAMPLES {
+            // partway through, it is the end of the output so far
+            let mut dec =
+                Explode::new_with(ExplodeConfig::new().dictionary(b"dict"));
+            assert_eq!(dec.window(), (&b"dict"[..], &[][..]));
+            let mut ours = b"dict".to_vec();
+            let mut i = 0;
+            while !dec.done() {
+                let (used, len) =
+                    dec.feed_slice_into(&encoded[i..], &mut buf).unwrap();
+                i += used;
+                ours.extend_from_slice(&buf[..len]);
+
+                let (a, b) = dec.window();
+                let start = ours.len().saturating_sub(4096);
+                assert_eq!(&ours[start..], &[a, b].concat()[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_with_window() {
+        let mut buf = [0; 7];
+        let mut storage = vec![0xaa; 5000];
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::with_window(&mut storage[..]);
+            let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            dec.check_invariants();
+
+            // owned storage can be taken back out
+            let config = ExplodeConfig::new().dictionary(b"unused");
+            let mut dec = Explode::with_window_config(vec![0; 4096], config);
+            let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            assert_eq!(dec.into_window().len(), 4096);
+        }
+        // nothing past the first 4096 bytes is touched
+        assert!(storage[4096..].iter().all(|b| *b == 0xaa));
+    }
+
+    #[test]
+    #[should_panic]
+    fn explode_with_window_too_small() {
+        Explode::with_window(vec![0; 1024]);
+    }
+
+    #[test]
+    fn explode_clone() {
+        use std::sync::{Arc, Mutex};
+
+        let mut buf = [0; 7];
+        for (encoded, decoded) in EXAMPLES {
+            let half = encoded.len() / 2;
+            let mut dec = Explode::new();
+            let mut n = 0u8;
+            dec.with_output_transform_cloneable(
+                TransformOrder::AfterWindow,
+                move |b| {
+                    n = n.wrapping_add(1);
+                    b ^ n
+                },
+            );
+            let checkpoints = Arc::new(Mutex::new(vec![]));
+            let saved = checkpoints.clone();
+            dec.on_checkpoint_cloneable(100, move |c| {
+                saved.lock().unwrap().push(c.output_position)
+            });
+            let mut first = vec![];
+            let mut i = 0;
+            while i < half {
+                let (used, len) =
+                    dec.feed_slice_into(&encoded[i..half], &mut buf).unwrap();
+                first.extend_from_slice(&buf[..len]);
+                i += used;
+            }
+
+            // both continue from the same place, hook state included
+            let mut other = dec.try_clone().unwrap();
+            let before = checkpoints.lock().unwrap().len();
+            let ours =
+                explode_all(&mut dec, &encoded[i..], &mut buf).unwrap();
+            let after = checkpoints.lock().unwrap().len();
+            let theirs =
+                explode_all(&mut other, &encoded[i..], &mut buf).unwrap();
+            assert_eq!(ours, theirs);
+            assert_eq!(dec.total_out(), other.total_out());
+
+            // and both call their own copy of the checkpoint hook
+            let checkpoints = checkpoints.lock().unwrap();
+            assert_eq!(checkpoints.len() - after, after - before);
+            assert_eq!(checkpoints[before..after], checkpoints[after..]);
+
+            first.extend_from_slice(&ours);
+            let expected: Vec<u8> = decoded
+                .iter()
+                .enumerate()
+                .map(|(i, b)| b ^ (i + 1) as u8)
+                .collect();
+            assert_eq!(expected, first);
+        }
+    }
+
+    #[test]
+    fn explode_phase() {
+        let (encoded, _) = EXAMPLES[2];
+        let mut dec = Explode::new();
+        assert_eq!(dec.phase(), Phase::Header);
+
+        // one byte of input and output at a time sees every phase
+        let mut seen = vec![];
+        let mut buf = [0; 1];
+        let mut i = 0;
+        while !dec.done() {
+            let (used, _) =
+                dec.feed_slice_into(&encoded[i..i + 1], &mut buf).unwrap();
+            i += used;
+            if !seen.contains(&dec.phase()) {
+                seen.push(dec.phase());
+            }
+        }
+        for phase in [
+            Phase::Header,
+            Phase::Literal,
+            Phase::Length,
+            Phase::Distance,
+            Phase::Copy,
+            Phase::Done,
+        ]
+        .iter()
+        {
+            assert!(seen.contains(phase), "{:?} not seen", phase);
+        }
+        assert_eq!(seen.last(), Some(&Phase::Done));
+
+        // the header comes from the config for headerless streams
+        let config = ExplodeConfig::new().headerless(false, 4);
+        assert_eq!(Explode::new_with(config).phase(), Phase::Literal);
+    }
+
+    #[test]
+    fn explode_status() {
+        let mut buf = [0; 7];
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            assert_eq!(dec.status(), Status::NeedsInput);
+
+            // feed in pieces; the status says what to do next
+            let mut ours = vec![];
+            let mut i = 0;
+            loop {
+                let end = (i + 3).min(encoded.len());
+                let (used, len) =
+                    dec.feed_slice_into(&encoded[i..end], &mut buf).unwrap();
+                i += used;
+                ours.extend_from_slice(&buf[..len]);
+                match dec.status() {
+                    Status::Finished => break,
+                    Status::NeedsInput => assert_eq!(i, end),
+                    Status::InProgress => assert_eq!(len, buf.len()),
+                }
+            }
+            assert_eq!(*decoded, &ours[..]);
+            assert_eq!(i, encoded.len());
+        }
+    }
+
+    #[test]
+    fn explode_bulk_copy() {
+        // overlapping copies, short and long, near and far
+        let mut out = BitWriter::new();
+        out.header(0, 6);
+        for b in b"abcdefgh" {
+            out.literal(*b);
+        }
+        for (len, dist) in [(518, 1), (300, 3), (2, 8), (517, 200), (9, 7)] {
+            out.copy(len, dist, 6);
+        }
+        out.end();
+        let runs = out.finish();
+        let mut cases: Vec<&[u8]> =
+            EXAMPLES.iter().map(|(e, _)| *e).collect();
+        cases.push(&runs);
+
+        for encoded in cases {
+            for size in [1, 7, 4096].iter() {
+                let mut buf = vec![0; *size];
+                let mut dec = Explode::new();
+                let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+
+                // a transform forces the byte at a time path
+                let mut dec = Explode::new();
+                dec.with_output_transform(TransformOrder::AfterWindow, |b| b);
+                let slow = explode_all(&mut dec, encoded, &mut buf).unwrap();
+                assert_eq!(ours, slow);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_hostile_feed() {
+        // a tiny generator, so this is repeatable
+        let mut seed = 0x2545_f491u32;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+
+        for (encoded, _) in EXAMPLES {
+            let mut dec = Explode::new();
+            let mut buf = [0; 7];
+            for _ in 0..10000 {
+                let pos = next() as usize % encoded.len();
+                let size = next() as usize % buf.len();
+                let mut decbuf = dec.with_buffer(&mut buf[..size]);
+                // any misuse may fail, but must not panic
+                let _ = match next() % 6 {
+                    0 => decbuf.feed(next() as u8),
+                    1 | 2 => decbuf.feed(encoded[pos]),
+                    3 => decbuf.feed_slice(&encoded[pos..]).map(|_| ()),
+                    4 => dec.finish(),
+                    _ => {
+                        if next() % 100 == 0 {
+                            dec.reset();
+                        }
+                        Ok(())
+                    }
+                };
+                dec.check_invariants();
+            }
+        }
+    }
+
+    #[test]
+    fn explode_invalid_code() {
+        use super::{ExplodeInput, ExplodeInputState};
+        use crate::codes::{CanonicalHuffman, FastTable};
+
+        // a single code of length 1, which leaves the other unused
+        static INCOMPLETE: CanonicalHuffman<&[u8]> =
+            unsafe { CanonicalHuffman::new(&[0, 1], &[7]) };
+        // with and without the lookup table
+        let fast = FastTable::new(&[0, 1], &[7]);
+        let empty = FastTable::new(&[0], &[]);
+        for (byte, expected) in
+            [(0xff, Ok(7)), (0x00, Err(Error::InvalidCode))]
+        {
+            for table in [&fast, &empty].iter() {
+                let mut input = ExplodeInput {
+                    next: ExplodeInputState::Available(byte),
+                    bitbuf: 0,
+                    bitcount: 0,
+                    consumed: 0,
+                };
+                let mut d = INCOMPLETE.decoder();
+                assert_eq!(input.decode(&mut d, table), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_output_limit() {
+        for (encoded, decoded) in EXAMPLES {
+            let len = decoded.len() as u64;
+            for size in [1, 7, 4096].iter() {
+                let mut buf = vec![0; *size];
+
+                // exactly enough is fine
+                let config = ExplodeConfig::new().output_limit(len);
+                let mut dec = Explode::new_with(config);
+                let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+
+                if len == 0 {
+                    continue;
+                }
+                let config = ExplodeConfig::new().output_limit(len - 1);
+                let mut dec = Explode::new_with(config);
+                let mut ours = vec![];
+                let result =
+                    explode_chunks(&mut dec, encoded, &mut buf, |c| {
+                        ours.extend_from_slice(c);
+                        Ok(())
+                    });
+                assert_eq!(result, Err(Error::OutputLimitExceeded));
+                assert_eq!(dec.total_out(), len - 1);
+                assert_eq!(decoded[..ours.len()], ours[..]);
+            }
+        }
+
+        // also with coded literals
+        let config = ExplodeConfig::new().output_limit(100);
+        let (encoded, _) = EXAMPLES[2];
+        assert_eq!(
+            explode_with_config(encoded, config),
+            Err(Error::OutputLimitExceeded)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explode_writer() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut ours = vec![];
+            let len = explode_to_writer(encoded, &mut ours).unwrap();
+            assert_eq!(len, decoded.len() as u64);
+            assert_eq!(*decoded, &ours[..]);
+        }
+
+        // errors from decompression and from the writer are passed on
+        let (encoded, _) = EXAMPLES[2];
+        let result = explode_to_writer(&encoded[..100], &mut vec![]);
+        assert_eq!(result, Err(Error::IncompleteInput));
+        let mut full = [0; 10];
+        let err = explode_to_writer(encoded, &mut &mut full[..]).unwrap_err();
+        match err {
+            Error::IO(e) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::WriteZero)
+            }
+            e => panic!("unexpected {:?}", e),
+        }
+    }
+
+    #[test]
+    fn explode_any_container() {
+        use alloc::boxed::Box;
+
+        for (encoded, decoded) in EXAMPLES {
+            let vec: Vec<u8> = encoded.to_vec();
+            assert_eq!(*decoded, &explode_any(vec).unwrap()[..]);
+            let boxed: Box<[u8]> = encoded.to_vec().into_boxed_slice();
+            assert_eq!(*decoded, &explode_any(boxed).unwrap()[..]);
+        }
+    }
+
+    #[test]
+    fn explode_empty() {
+        assert_eq!(uncoded(b""), vec![0x00, 0x04, 0x01, 0xff]);
+        let ours = explode(&uncoded(b"")).unwrap();
+        assert!(ours.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explode_parallel() {
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..50 {
+                        for (encoded, decoded) in EXAMPLES {
+                            let ours = explode_pooled(encoded).unwrap();
+                            assert_eq!(*decoded, &ours[..]);
+                        }
+                    }
+                });
+            }
+        });
+
+        // errors don't poison the pool
+        assert!(explode_pooled(&[0x00]).is_err());
+        let (encoded, decoded) = EXAMPLES[0];
+        assert_eq!(decoded, &explode_pooled(encoded).unwrap()[..]);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn explode_bytes() {
+        for (encoded, decoded) in EXAMPLES {
+            let ours = super::explode_to_bytes(encoded).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn explode_buf() {
+        use bytes::Buf;
+
+        // a Buf with chunks of at most n bytes
+        struct Chunked<'a>(&'a [u8], usize);
+        impl Buf for Chunked<'_> {
+            fn remaining(&self) -> usize {
+                self.0.len()
+            }
+            fn chunk(&self) -> &[u8] {
+                &self.0[..self.1.min(self.0.len())]
+            }
+            fn advance(&mut self, cnt: usize) {
+                self.0 = &self.0[cnt..];
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let mut data = encoded.to_vec();
+            data.extend_from_slice(b"extra");
+            for n in [1, 2, 7, 10000].iter() {
+                let mut src = Chunked(&data, *n);
+                let mut dst = vec![];
+                super::explode_buf(&mut src, &mut dst).unwrap();
+                assert_eq!(*decoded, &dst[..]);
+                assert_eq!(src.0, b"extra");
+            }
+
+            // exactly enough room, and not enough
+            let mut out = vec![0; decoded.len()];
+            let mut dst = &mut out[..];
+            super::explode_buf(&mut &encoded[..], &mut dst).unwrap();
+            assert!(dst.is_empty());
+            assert_eq!(*decoded, &out[..]);
+            if !decoded.is_empty() {
+                let mut out = vec![0; decoded.len() - 1];
+                assert_eq!(
+                    super::explode_buf(&mut &encoded[..], &mut &mut out[..]),
+                    Err(Error::OutputFull)
+                );
+            }
+
+            let mut cut = &encoded[..encoded.len() - 1];
+            assert_eq!(
+                super::explode_buf(&mut cut, &mut vec![]),
+                Err(Error::IncompleteInput)
+            );
+        }
+    }
+
+    #[test]
+    fn explode_small() {
+        let mut buf = [0; 1];
+        for (encoded, decoded) in EXAMPLES {
+            let ours = explode_with_buffer(encoded, &mut buf).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    fn explode_incomplete() {
+        for (encoded, _) in EXAMPLES {
+            let ours = explode(&encoded[..encoded.len() - 1]);
+            match ours {
+                Err(Error::IncompleteInput) => (),
+                _ => panic!("incorrectly parsed incomplete input"),
+            }
+        }
+    }
+
+    #[test]
+    fn explode_extra() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut encodedplus: Vec<u8> = encoded.to_vec();
+            encodedplus.push(42);
+            let ours = explode(&encodedplus).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    fn explode_discarding() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            dec.run_discarding(encoded).unwrap();
+            assert!(dec.done());
+
+            let start = decoded.len().saturating_sub(4096);
+            let (a, b) = dec.window();
+            assert_eq!(&decoded[start..], &[a, b].concat()[..]);
+        }
+    }
+
+    #[test]
+    fn explode_discarding_incomplete() {
+        for (encoded, _) in EXAMPLES {
+            let mut dec = Explode::new();
+            match dec.run_discarding(&encoded[..encoded.len() - 1]) {
+                Err(Error::IncompleteInput) => (),
+                _ => panic!("incorrectly parsed incomplete input"),
+            }
+        }
+    }
+
+    #[test]
+    fn explode_bytes_iter() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            let ours: Vec<u8> =
+                dec.bytes(encoded).collect::<Result<_, _>>().unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            assert!(dec.done());
+            assert_eq!(dec.bytes(encoded).count(), 0);
+
+            // errors end the iteration
+            let mut dec = Explode::new();
+            let cut = &encoded[..encoded.len() - 1];
+            let mut iter = dec.bytes(cut).skip_while(|b| b.is_ok());
+            assert_eq!(iter.next(), Some(Err(Error::IncompleteInput)));
+            assert_eq!(iter.next(), None);
+        }
+
+        let mut dec = Explode::new();
+        let mut iter = dec.bytes(&[0x00, 0x07]);
+        assert_eq!(iter.next(), Some(Err(Error::BadDictionary(7))));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn explode_transcoded() {
+        // ASCII, plus a few CP437 characters
+        let mut map = ['\u{fffd}'; 256];
+        for (i, c) in map.iter_mut().enumerate().take(128) {
+            *c = i as u8 as char;
+        }
+        map[0x82] = 'é';
+        map[0xb0] = '░';
+        map[0xe1] = 'ß';
+
+        let encoded = uncoded(b"caf\x82 \xb0\xb0 stra\xe1e \xff");
+        let ours = explode_transcode(&encoded, &map).unwrap();
+        assert_eq!(ours, "café ░░ straße \u{fffd}");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explode_headerless() {
+        use std::io::Read;
+
+        for (encoded, decoded) in EXAMPLES {
+            let config =
+                ExplodeConfig::new().headerless(encoded[0] == 1, encoded[1]);
+            let dec = Explode::new_with(config);
+            let mut r = dec.into_reader(&encoded[2..]);
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn explode_headerless_bad_dictionary() {
+        ExplodeConfig::new().headerless(false, 7);
+    }
+
+    #[test]
+    fn explode_max_distance() {
+        // the largest distances possible with a 1K dictionary
+        let data: Vec<u8> = (0..1024).map(|i| (i * 7) as u8).collect();
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        for b in data.iter() {
+            out.literal(*b);
+        }
+        out.copy(3, 1024, 4);
+        out.copy(2, 256, 4);
+        out.end();
+
+        let mut expected = data.clone();
+        expected.extend_from_slice(&data[..3]);
+        expected.extend_from_slice(&data[771..773]);
+        assert_eq!(explode(&out.finish()).unwrap(), expected);
+
+        // one byte short of reaching that far
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        for b in data[1..].iter() {
+            out.literal(*b);
+        }
+        out.copy(3, 1024, 4);
+        out.end();
+        assert_eq!(
+            explode(&out.finish()),
+            Err(Error::BadDistance {
+                distance: 1024,
+                window_len: 1023,
+            })
+        );
+    }
+
+    #[test]
+    fn explode_coded_small() {
+        // every byte, including all the 13-bit literal codes
+        let data: Vec<u8> = (0..=255).chain((0..=255).rev()).collect();
+        assert_eq!(tables::LITERAL.encode(0xff), Some((8191, 13)));
+
+        let mut out = BitWriter::new();
+        out.header(1, 6);
+        for b in data.iter() {
+            out.literal_coded(*b);
+        }
+        out.end();
+        let encoded = out.finish();
+
+        // feed one byte at a time, with room for one byte of output
+        let mut buf = [0; 1];
+        let ours = explode_with_buffer(&encoded, &mut buf).unwrap();
+        assert_eq!(data, ours);
+
+        // and drive it by hand, to count the feed cycles
+        let mut dec = Explode::new();
+        let mut decbuf = dec.with_buffer(&mut buf);
+        let mut ours = vec![];
+        let mut i = 0;
+        let mut full = 0;
+        assert!(decbuf.wants_input());
+        while !decbuf.done() {
+            let result = decbuf.feed(encoded[i]);
+            assert_eq!(result.is_err(), decbuf.wants_input());
+            match result {
+                Ok(()) => {
+                    full += 1;
+                    ours.extend_from_slice(decbuf.get());
+                    decbuf.reset();
+                }
+                Err(Error::IncompleteInput) => i += 1,
+                Err(e) => panic!("{:?}", e),
+            }
+        }
+        assert_eq!(data, ours);
+        assert_eq!(full, data.len());
+        assert_eq!(i, encoded.len() - 1);
+    }
 
     #[test]
-    fn explode_simple() {
+    fn explode_tolerant() {
+        let data = b"a stream with no end code";
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        for b in data.iter() {
+            out.literal(*b);
+        }
+        out.copy(10, 10, 4);
+        let encoded = out.finish();
+        let mut expected = data.to_vec();
+        expected.extend_from_slice(&data[data.len() - 10..]);
+
+        let mut buf = [0; 7];
+        let config = ExplodeConfig::new().tolerant(true);
+        let mut dec = Explode::new_with(config);
+        let ours = explode_all(&mut dec, &encoded, &mut buf).unwrap();
+        assert_eq!(expected, ours);
+        assert!(dec.done());
+        assert!(dec.ended_without_marker());
+        assert_eq!(dec.end_reason(), Some(EndReason::MissingEndCode));
+
+        // without tolerance, this is an error
+        match explode(&encoded) {
+            Err(Error::IncompleteInput) => (),
+            _ => panic!("incorrectly parsed stream without end code"),
+        }
+
+        // streams with an end code are unaffected
         for (encoded, decoded) in EXAMPLES {
-            let ours = explode(encoded).unwrap();
+            let config = ExplodeConfig::new().tolerant(true);
+            let mut dec = Explode::new_with(config);
+            let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
             assert_eq!(*decoded, &ours[..]);
+            assert!(!dec.ended_without_marker());
+            assert_eq!(dec.end_reason(), Some(EndReason::EndCode));
         }
     }
 
     #[test]
-    fn explode_small() {
-        let mut buf = [0; 1];
+    fn explode_tolerant_mid_token() {
+        // 'A', then a copy token that spans three bytes
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        out.literal(b'A');
+        out.copy(300, 1, 4);
+        out.end();
+        let encoded = out.finish();
+
+        // the copy starts in the last byte, so this looks like padding
+        let mut buf = [0; 16];
+        let config = ExplodeConfig::new().tolerant(true);
+        let mut dec = Explode::new_with(config);
+        let ours = explode_all(&mut dec, &encoded[..4], &mut buf).unwrap();
+        assert_eq!(ours, b"A");
+        assert!(dec.ended_without_marker());
+
+        // but this is in the middle of the copy
+        let config = ExplodeConfig::new().tolerant(true);
+        let mut dec = Explode::new_with(config);
+        match explode_all(&mut dec, &encoded[..5], &mut buf) {
+            Err(Error::IncompleteInput) => (),
+            _ => panic!("incorrectly parsed truncated token"),
+        }
+        assert!(!dec.done());
+        assert!(!dec.ended_without_marker());
+        assert_eq!(dec.end_reason(), None);
+
+        // truncated header is also an error
+        let config = ExplodeConfig::new().tolerant(true);
+        let mut dec = Explode::new_with(config);
+        match explode_all(&mut dec, &encoded[..1], &mut buf) {
+            Err(Error::IncompleteInput) => (),
+            _ => panic!("incorrectly parsed truncated header"),
+        }
+    }
+
+    #[test]
+    fn explode_feed_slice_into() {
         for (encoded, decoded) in EXAMPLES {
-            let ours = explode_with_buffer(encoded, &mut buf).unwrap();
-            assert_eq!(*decoded, &ours[..]);
+            for outlen in [1, 2, 3, 7, 64, 4096].iter() {
+                for inlen in [1, 2, 5, 64, 4096].iter() {
+                    let mut dec = Explode::new();
+                    let mut buf = vec![0; *outlen];
+                    let mut ours = vec![];
+                    let mut i = 0;
+                    while !dec.done() {
+                        let end = std::cmp::min(i + inlen, encoded.len());
+                        let (used, len) = dec
+                            .feed_slice_into(&encoded[i..end], &mut buf)
+                            .unwrap();
+                        dec.check_invariants();
+                        assert!(used > 0 || len > 0 || dec.done());
+                        assert!(len <= buf.len());
+                        i += used;
+                        ours.extend_from_slice(&buf[..len]);
+                    }
+                    assert_eq!(*decoded, &ours[..]);
+                    assert_eq!(i, encoded.len());
+
+                    // once done, nothing more is used or produced
+                    assert_eq!(
+                        dec.feed_slice_into(&[42], &mut buf).unwrap(),
+                        (0, 0)
+                    );
+                }
+            }
         }
     }
 
     #[test]
-    fn explode_incomplete() {
+    fn explode_feed_slice_into_errors() {
+        let mut dec = Explode::new();
+        let mut buf = [0; 16];
+        match dec.feed_slice_into(&[0x00, 0x02], &mut buf) {
+            Err(Error::BadDictionary(2)) => (),
+            _ => panic!("did not detect bad dictionary"),
+        }
+
+        // running out of input is not an error
+        let (encoded, _) = EXAMPLES[1];
+        let mut dec = Explode::new();
+        let half = encoded.len() / 2;
+        let mut i = 0;
+        while i < half {
+            i += dec.feed_slice_into(&encoded[i..half], &mut buf).unwrap().0;
+        }
+        assert!(!dec.done());
+        assert_eq!(dec.feed_slice_into(&[], &mut buf).unwrap().0, 0);
+    }
+
+    #[test]
+    fn explode_buffer_feed_slice() {
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 7, 4096].iter() {
+                let mut dec = Explode::new();
+                let mut buf = vec![0; *size];
+                let mut decbuf = dec.with_buffer(&mut buf);
+                let mut ours = vec![];
+                let mut i = 0;
+                while !decbuf.done() {
+                    let end = (i + 5).min(encoded.len());
+                    i += decbuf.feed_slice(&encoded[i..end]).unwrap();
+                    ours.extend_from_slice(decbuf.get());
+                    decbuf.reset();
+                }
+                assert_eq!(i, encoded.len());
+                assert_eq!(*decoded, &ours[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_buffer_empty() {
+        let (encoded, _) = EXAMPLES[0];
+        let mut dec = Explode::new();
+        let mut buf = [0; 4];
+        let mut decbuf = dec.with_buffer(&mut buf);
+        assert!(decbuf.is_empty());
+        assert_eq!(decbuf.get(), &[]);
+
+        decbuf.feed_slice(encoded).unwrap();
+        assert!(!decbuf.is_empty());
+        assert_eq!(decbuf.len(), decbuf.get().len());
+
+        decbuf.reset();
+        assert!(decbuf.is_empty());
+        assert_eq!(decbuf.len(), 0);
+        assert_eq!(decbuf.get(), &[]);
+    }
+
+    #[test]
+    fn explode_buffer_feed_from() {
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 7, 4096].iter() {
+                let mut data = encoded.to_vec();
+                data.push(42);
+                let mut input = data.into_iter();
+
+                let mut dec = Explode::new();
+                let mut buf = vec![0; *size];
+                let mut decbuf = dec.with_buffer(&mut buf);
+                let mut ours = vec![];
+                while !decbuf.done() {
+                    assert!(decbuf.feed_from(&mut input).unwrap());
+                    ours.extend_from_slice(decbuf.get());
+                    decbuf.reset();
+                }
+                assert_eq!(*decoded, &ours[..]);
+                // the extra byte is never taken
+                assert_eq!(input.collect::<Vec<_>>(), [42]);
+            }
+
+            // running out of input is not an error
+            let mut dec = Explode::new();
+            let mut buf = vec![0; decoded.len() + 1];
+            let mut decbuf = dec.with_buffer(&mut buf);
+            let half = &encoded[..encoded.len() / 2];
+            assert!(!decbuf.feed_from(&mut half.iter().copied()).unwrap());
+            let rest = &encoded[encoded.len() / 2..];
+            assert!(decbuf.feed_from(&mut rest.iter().copied()).unwrap());
+            assert!(decbuf.done());
+            assert_eq!(decbuf.get(), *decoded);
+        }
+    }
+
+    #[test]
+    fn explode_invariants() {
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 7, 4096].iter() {
+                let mut dec = Explode::new();
+                let mut buf = vec![0; *size];
+                let mut decbuf = dec.with_buffer(&mut buf);
+                let mut ours = vec![];
+                let mut i = 0;
+                decbuf.check_invariants();
+                while !decbuf.done() {
+                    let result = decbuf.feed(encoded[i]);
+                    decbuf.check_invariants();
+                    match result {
+                        Ok(()) => {
+                            ours.extend_from_slice(decbuf.get());
+                            decbuf.reset();
+                        }
+                        Err(Error::IncompleteInput) => i += 1,
+                        Err(e) => panic!("{:?}", e),
+                    }
+                }
+                assert_eq!(*decoded, &ours[..]);
+                dec.check_invariants();
+            }
+        }
+    }
+
+    #[test]
+    fn explode_bit_position() {
         for (encoded, _) in EXAMPLES {
-            let ours = explode(&encoded[..encoded.len() - 1]);
-            match ours {
+            let mut dec = Explode::new();
+            dec.run_discarding(encoded).unwrap();
+            let pos = dec.bit_position();
+            assert_eq!(pos.div_ceil(8), encoded.len() as u64);
+        }
+    }
+
+    #[test]
+    fn explode_embedded() {
+        // a stream between 3 bits and 5 bits of other data
+        let data = b"embedded in other bits";
+        let mut out = BitWriter::new();
+        out.bits(0b101, 3);
+        out.header(0, 4);
+        for b in data.iter() {
+            out.literal(*b);
+        }
+        out.copy(3, 3, 4);
+        out.end();
+        let end = out.position();
+        out.bits(0b10110, 5);
+        let encoded = out.finish();
+
+        let config = ExplodeConfig::new().bit_offset(3);
+        let mut dec = Explode::new_with(config);
+        let mut buf = [0; 64];
+        let (_, len) = dec.feed_slice_into(&encoded, &mut buf).unwrap();
+        assert!(dec.done());
+        assert_eq!(&buf[..len - 3], &data[..]);
+        assert_eq!(dec.bit_position(), end);
+
+        // read the other bits back out
+        let pos = dec.bit_position();
+        let i = pos as usize / 8;
+        let rest = encoded[i] as u32
+            | (*encoded.get(i + 1).unwrap_or(&0) as u32) << 8;
+        assert_eq!((rest >> (pos % 8)) & 0b11111, 0b10110);
+    }
+
+    #[test]
+    fn explode_reset() {
+        let mut dec = Explode::new();
+        let mut buf = [0; 16384];
+        for _ in 0..2 {
+            for (encoded, decoded) in EXAMPLES {
+                dec.reset();
+                let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+            }
+        }
+
+        // a reset in the middle of decoding
+        let (encoded, decoded) = EXAMPLES[2];
+        dec.reset();
+        dec.feed_slice_into(&encoded[..100], &mut buf).unwrap();
+        dec.reset();
+        let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+        assert_eq!(decoded, &ours[..]);
+    }
+
+    #[test]
+    fn explode_reset_with_config() {
+        let mut dec = Explode::new();
+        let mut buf = [0; 64];
+        for i in 0..4 {
+            for (encoded, decoded) in EXAMPLES {
+                let (config, input) = if i % 2 == 0 {
+                    (ExplodeConfig::new(), &encoded[..])
+                } else {
+                    let config = ExplodeConfig::new()
+                        .headerless(encoded[0] == 1, encoded[1]);
+                    (config, &encoded[2..])
+                };
+                dec.reset_with_config(config);
+                let ours = explode_all(&mut dec, input, &mut buf).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+
+                // plain reset keeps the configuration
+                dec.reset();
+                let ours = explode_all(&mut dec, input, &mut buf).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_end_reason() {
+        let mut buf = [0; 16384];
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            assert_eq!(dec.end_reason(), None);
+
+            // unset while still in progress
+            let half = encoded.len() / 2;
+            let (used, _) =
+                dec.feed_slice_into(&encoded[..half], &mut buf).unwrap();
+            assert_eq!(used, half);
+            assert_eq!(dec.end_reason(), None);
+
+            let ours =
+                explode_all(&mut dec, &encoded[half..], &mut buf).unwrap();
+            assert!(decoded.ends_with(&ours));
+            assert_eq!(dec.end_reason(), Some(EndReason::EndCode));
+
+            // and cleared on reset
+            dec.reset();
+            assert_eq!(dec.end_reason(), None);
+        }
+    }
+
+    #[test]
+    fn explode_header() {
+        let mut buf = [0; 16384];
+        for (encoded, _) in EXAMPLES {
+            let expected = Header {
+                coded_literals: encoded[0] == 1,
+                dict_size: encoded[1],
+            };
+
+            let mut dec = Explode::new();
+            assert_eq!(dec.header(), None);
+            explode_all(&mut dec, encoded, &mut buf).unwrap();
+            assert_eq!(dec.header(), Some(expected));
+
+            let config = ExplodeConfig::new()
+                .headerless(expected.coded_literals, expected.dict_size);
+            let dec = Explode::new_with(config);
+            assert_eq!(dec.header(), Some(expected));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explode_copy_to() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            let mut ours = vec![];
+            let len = dec.copy_to(&encoded[..], &mut ours).unwrap();
+            assert_eq!(len, decoded.len() as u64);
+            assert_eq!(*decoded, &ours[..]);
+            assert!(dec.done());
+
+            // starting from a headerless decompressor
+            let config =
+                ExplodeConfig::new().headerless(encoded[0] == 1, encoded[1]);
+            let mut dec = Explode::new_with(config);
+            let mut ours = vec![];
+            dec.copy_to(&encoded[2..], &mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            // starting part way through
+            let mut dec = Explode::new();
+            let mut buf = [0; 16384];
+            let half = encoded.len() / 2;
+            let (used, len) =
+                dec.feed_slice_into(&encoded[..half], &mut buf).unwrap();
+            assert_eq!(used, half);
+            let mut ours = buf[..len].to_vec();
+            dec.copy_to(&encoded[half..], &mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+
+        // running out of input is an error
+        let (encoded, _) = EXAMPLES[2];
+        let mut dec = Explode::new();
+        match dec.copy_to(&encoded[..100], &mut std::io::sink()) {
+            Err(Error::IncompleteInput) => (),
+            _ => panic!("did not detect truncated stream"),
+        }
+    }
+
+    #[test]
+    fn explode_capacity_hint() {
+        for (encoded, decoded) in EXAMPLES {
+            for hint in [0, 1, decoded.len(), 100000].iter() {
+                let config = ExplodeConfig::new().capacity_hint(*hint);
+                let ours = explode_with_config(encoded, config).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+                assert!(ours.capacity() >= *hint);
+            }
+        }
+    }
+
+    #[test]
+    fn explode_skip_prefix() {
+        let mut buf = [0; 16384];
+        for (encoded, decoded) in EXAMPLES {
+            let mut data = b"JUNK".to_vec();
+            data.extend_from_slice(encoded);
+
+            let mut dec = Explode::new();
+            dec.skip_prefix(4);
+            let ours = explode_all(&mut dec, &data, &mut buf).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            // one byte at a time, in two steps
+            let mut dec = Explode::new();
+            dec.skip_prefix(1);
+            dec.skip_prefix(3);
+            let ours = explode_all(&mut dec, &data, &mut buf[..1]).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            // headerless streams skip before the stream too
+            let config =
+                ExplodeConfig::new().headerless(encoded[0] == 1, encoded[1]);
+            let mut dec = Explode::new_with(config);
+            dec.skip_prefix(6);
+            let ours = explode_all(&mut dec, &data, &mut buf).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn explode_skip_prefix_late() {
+        let (encoded, _) = EXAMPLES[0];
+        let mut dec = Explode::new();
+        let mut buf = [0; 1];
+        dec.feed_slice_into(&encoded[..1], &mut buf).unwrap();
+        dec.skip_prefix(4);
+    }
+
+    #[test]
+    fn explode_header_bytes_needed() {
+        let (encoded, _) = EXAMPLES[0];
+        let mut buf = [0; 16];
+
+        let mut dec = Explode::new();
+        assert_eq!(dec.header_bytes_needed(), 2);
+        dec.feed_slice_into(&encoded[..1], &mut buf).unwrap();
+        assert_eq!(dec.header_bytes_needed(), 1);
+        dec.feed_slice_into(&encoded[1..2], &mut buf).unwrap();
+        assert_eq!(dec.header_bytes_needed(), 0);
+
+        // skipped input counts too
+        let config = ExplodeConfig::new().bit_offset(3);
+        let mut dec = Explode::new_with(config);
+        assert_eq!(dec.header_bytes_needed(), 3);
+        dec.skip_prefix(2);
+        assert_eq!(dec.header_bytes_needed(), 5);
+        dec.feed_slice_into(&[0, 0, 0], &mut buf).unwrap();
+        assert_eq!(dec.header_bytes_needed(), 2);
+
+        let config = ExplodeConfig::new().headerless(false, 4);
+        let dec = Explode::new_with(config);
+        assert_eq!(dec.header_bytes_needed(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explode_copy_to_write_zero() {
+        // a sink that refuses all data
+        struct Full;
+        impl std::io::Write for Full {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Ok(0)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (encoded, _) = EXAMPLES[0];
+        let mut dec = Explode::new();
+        match dec.copy_to(encoded, &mut Full) {
+            Err(Error::IO(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::WriteZero)
+            }
+            _ => panic!("did not detect full sink"),
+        }
+
+        // empty output never writes, so is fine
+        let (encoded, _) = EXAMPLES[1];
+        let mut dec = Explode::new();
+        assert_eq!(dec.copy_to(encoded, &mut Full).unwrap(), 0);
+    }
+
+    #[test]
+    fn explode_decode_exact_into() {
+        for (encoded, decoded) in EXAMPLES {
+            // exactly the right size
+            let mut out = vec![0; decoded.len()];
+            let mut dec = Explode::new();
+            dec.decode_exact_into(encoded, &mut out).unwrap();
+            assert_eq!(*decoded, &out[..]);
+
+            // shorter output ignores the rest of the stream
+            let half = decoded.len() / 2;
+            let mut out = vec![0; half];
+            let mut dec = Explode::new();
+            dec.decode_exact_into(encoded, &mut out).unwrap();
+            assert_eq!(&decoded[..half], &out[..]);
+
+            // longer output is an error
+            let mut out = vec![0; decoded.len() + 1];
+            let mut dec = Explode::new();
+            match dec.decode_exact_into(encoded, &mut out) {
                 Err(Error::IncompleteInput) => (),
-                _ => panic!("incorrectly parsed incomplete input"),
+                _ => panic!("filled output past end of stream"),
             }
         }
+
+        // as is running out of input
+        let (encoded, decoded) = EXAMPLES[2];
+        let mut out = vec![0; decoded.len()];
+        let mut dec = Explode::new();
+        match dec.decode_exact_into(&encoded[..100], &mut out) {
+            Err(Error::IncompleteInput) => (),
+            _ => panic!("did not detect truncated stream"),
+        }
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn explode_extra() {
+    fn explode_read_adapter() {
+        use std::io::Read;
+
         for (encoded, decoded) in EXAMPLES {
-            let mut encodedplus: Vec<u8> = encoded.iter().cloned().collect();
-            encodedplus.push(42);
-            let ours = explode(&encodedplus).unwrap();
+            let mut dec = Explode::new();
+            let mut inner = &encoded[..];
+
+            // read some of the output, then let the adapter go
+            let mut ours = vec![0; decoded.len() / 2];
+            dec.read_adapter(&mut inner).read_exact(&mut ours).unwrap();
+
+            // and then continue with the same decompressor and input
+            let mut rest = vec![];
+            dec.copy_to(&mut inner, &mut rest).unwrap();
+            ours.extend_from_slice(&rest);
+            assert_eq!(*decoded, &ours[..]);
+            assert!(dec.done());
+
+            // reading after the end gives nothing
+            let mut buf = [0; 16];
+            assert_eq!(
+                dec.read_adapter(&mut inner).read(&mut buf).unwrap(),
+                0
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn explode_copy_to_with_checksum() {
+        // count bytes, and xor them together
+        struct Xor(u8, usize);
+        impl crate::Checksum for Xor {
+            fn update(&mut self, data: &[u8]) {
+                self.0 = data.iter().fold(self.0, |a, b| a ^ b);
+                self.1 += data.len();
+            }
+            fn finalize(self) -> u64 {
+                self.0 as u64
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            let mut sum = Xor(0, 0);
+            let mut ours = vec![];
+            dec.copy_to_with_checksum(*encoded, &mut ours, &mut sum)
+                .unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            assert_eq!(sum.1, decoded.len());
+            let expected = decoded.iter().fold(0, |a, b| a ^ b);
+            assert_eq!(crate::Checksum::finalize(sum), expected as u64);
+        }
+    }
+
+    #[test]
+    fn explode_checkpoints() {
+        use std::sync::{Arc, Mutex};
+
+        let mut buf = [0; 100];
+        for (encoded, decoded) in EXAMPLES {
+            let checkpoints = Arc::new(Mutex::new(vec![]));
+            let saved = checkpoints.clone();
+            let mut dec = Explode::new();
+            dec.on_checkpoint(1000, move |c| saved.lock().unwrap().push(c));
+            let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            let end = dec.bit_position();
+            let checkpoints: Vec<Checkpoint> =
+                checkpoints.lock().unwrap().clone();
+            assert_eq!(checkpoints.len(), decoded.len() / 1000);
+            for (i, c) in checkpoints.iter().enumerate() {
+                let pos = c.output_position as usize;
+                assert!(pos >= (i + 1) * 1000 && pos < (i + 1) * 1000 + 518);
+                let window_size = c.header.window_size();
+                assert_eq!(c.window.len(), pos.min(window_size));
+                assert_eq!(c.window, &decoded[pos - c.window.len()..pos]);
+
+                // restart from the checkpoint
+                let mut dec = Explode::new();
+                dec.restore_state(c);
+                assert_eq!(dec.bit_position(), c.bit_position & !7);
+                let start = (c.bit_position / 8) as usize;
+                let rest = explode_all(&mut dec, &encoded[start..], &mut buf)
+                    .unwrap();
+                assert_eq!(&decoded[pos..], &rest[..]);
+                assert_eq!(dec.bit_position(), end);
+
+                // an output transform set before restoring is kept
+                let mut dec = Explode::new();
+                dec.with_output_transform(TransformOrder::AfterWindow, |b| {
+                    b.to_ascii_uppercase()
+                });
+                dec.restore_state(c);
+                let rest = explode_all(&mut dec, &encoded[start..], &mut buf)
+                    .unwrap();
+                assert_eq!(decoded[pos..].to_ascii_uppercase(), rest);
+            }
+        }
+
+        // callbacks need not be Sync
+        let (encoded, _) = EXAMPLES[2];
+        let last = std::cell::Cell::new(0);
+        let mut dec = Explode::new();
+        dec.on_checkpoint(1000, move |c| {
+            assert!(c.output_position / 1000 > last.get() / 1000);
+            last.set(c.output_position);
+        });
+        explode_all(&mut dec, encoded, &mut buf).unwrap();
+
+        // or Clone
+        struct Count(usize);
+        let mut count = Count(0);
+        let mut dec = Explode::new();
+        dec.on_checkpoint(1000, move |_| {
+            count.0 += 1;
+            assert!(count.0 <= 10);
+        });
+        explode_all(&mut dec, encoded, &mut buf).unwrap();
+    }
+
+    #[test]
+    fn explode_clone_not_cloneable() {
+        struct Key(u8);
+        let key = Key(0x5a);
+        let mut dec = Explode::new();
+        assert!(dec.try_clone().is_some());
+        dec.with_output_transform(TransformOrder::AfterWindow, move |b| {
+            b ^ key.0
+        });
+        assert!(dec.try_clone().is_none());
+
+        let mut dec = Explode::new();
+        dec.on_checkpoint(100, |_| ());
+        assert!(dec.try_clone().is_none());
+
+        // cloneable hooks can be copied again once the others are gone
+        dec.on_checkpoint_cloneable(100, |_| ());
+        assert!(dec.try_clone().is_some());
+    }
+
+    #[test]
+    fn explode_output_transform() {
+        let mut buf = [0; 7];
+        for (encoded, decoded) in EXAMPLES {
+            let mut dec = Explode::new();
+            dec.with_output_transform(TransformOrder::AfterWindow, |b| {
+                b ^ 0x5a
+            });
+            let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+            let expected: Vec<u8> =
+                decoded.iter().map(|b| b ^ 0x5a).collect();
+            assert_eq!(expected, ours);
+
+            // reset removes the transform
+            dec.reset();
+            let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
             assert_eq!(*decoded, &ours[..]);
         }
+
+        // copies see the transformed bytes only when applied before
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        out.literal(b'a');
+        out.literal(b'b');
+        out.copy(4, 2, 4);
+        out.end();
+        let encoded = out.finish();
+        for (order, expected) in [
+            (TransformOrder::BeforeWindow, b"`cab`c"),
+            (TransformOrder::AfterWindow, b"`c`c`c"),
+        ]
+        .iter()
+        {
+            let mut dec = Explode::new();
+            dec.with_output_transform(*order, |b| b ^ 1);
+            let ours = explode_all(&mut dec, &encoded, &mut buf).unwrap();
+            assert_eq!(&expected[..], &ours[..]);
+        }
+
+        // a rolling key, kept in a Cell, which is not Sync
+        let key = b"key";
+        let (encoded, decoded) = EXAMPLES[0];
+        let pos = std::cell::Cell::new(0);
+        let mut dec = Explode::new();
+        dec.with_output_transform(TransformOrder::AfterWindow, move |b| {
+            pos.set(pos.get() + 1);
+            b ^ key[(pos.get() - 1) % key.len()]
+        });
+        let ours = explode_all(&mut dec, encoded, &mut buf).unwrap();
+        let expected: Vec<u8> = decoded
+            .iter()
+            .zip(key.iter().cycle())
+            .map(|(b, k)| b ^ k)
+            .collect();
+        assert_eq!(expected, ours);
+    }
+
+    #[test]
+    fn explode_decoded_len_upper_bound() {
+        for (encoded, decoded) in EXAMPLES {
+            let bound = Explode::decoded_len_upper_bound(encoded);
+            assert!(bound >= decoded.len());
+        }
+        assert_eq!(Explode::decoded_len_upper_bound(&[]), 0);
+        assert_eq!(Explode::decoded_len_upper_bound(&[0x02, 0x04, 0]), 0);
+        assert_eq!(Explode::decoded_len_upper_bound(&[0x00, 0x07, 0]), 0);
+        assert_eq!(Explode::decoded_len_upper_bound(&[0x00, 0x04]), 0);
+
+        // the worst case is a stream of long copies, which comes close
+        for dict in 4..=6 {
+            let mut out = BitWriter::new();
+            out.header(0, dict);
+            out.literal(0);
+            for _ in 0..1000 {
+                out.copy(518, 1, dict);
+            }
+            out.end();
+            let encoded = out.finish();
+            let len = explode(&encoded).unwrap().len();
+            let bound = Explode::decoded_len_upper_bound(&encoded);
+            assert!(bound >= len);
+            assert!(bound - len < 1000, "{} is far from {}", bound, len);
+        }
+    }
+
+    #[test]
+    fn explode_precise_report() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut encodedplus = encoded.to_vec();
+            encodedplus.push(0x42);
+            let report = explode_precise(&encodedplus).unwrap();
+            assert_eq!(*decoded, &report.output[..]);
+            assert_eq!(report.input_bytes_consumed, encoded.len());
+
+            let (output, used) = explode_counted(&encodedplus).unwrap();
+            assert_eq!(*decoded, &output[..]);
+            assert_eq!(used, encoded.len());
+        }
+
+        // header and end code only is exactly 32 bits
+        let report = explode_precise(&[0x00, 0x04, 0x01, 0xff]).unwrap();
+        assert_eq!(report.input_bytes_consumed, 4);
+        assert_eq!(report.end_bit_offset, 0);
+
+        // one more literal is 9 more bits
+        let report = explode_precise(&uncoded(b"x")).unwrap();
+        assert_eq!(report.output, b"x");
+        assert_eq!(report.input_bytes_consumed, 6);
+        assert_eq!(report.end_bit_offset, 1);
+
+        assert!(explode_precise(&[0x00, 0x04]).is_err());
+    }
+
+    #[test]
+    fn explode_into_slice() {
+        for (encoded, decoded) in EXAMPLES {
+            // exactly enough room, and more
+            let mut out = vec![0; decoded.len() + 1];
+            let len =
+                explode_into(encoded, &mut out[..decoded.len()]).unwrap();
+            assert_eq!(*decoded, &out[..len]);
+            let len = explode_into(encoded, &mut out).unwrap();
+            assert_eq!(*decoded, &out[..len]);
+
+            if !decoded.is_empty() {
+                let short = &mut out[..decoded.len() - 1];
+                assert_eq!(
+                    explode_into(encoded, short),
+                    Err(Error::OutputFull)
+                );
+            }
+            let cut = &encoded[..encoded.len() - 1];
+            assert_eq!(
+                explode_into(cut, &mut out),
+                Err(Error::IncompleteInput)
+            );
+        }
+        assert_eq!(explode_into(&uncoded(b""), &mut []), Ok(0));
+    }
+
+    #[test]
+    fn explode_offset() {
+        for (encoded, decoded) in EXAMPLES {
+            for prefix in [&b""[..], b"\x00", b"junk", &[0xff; 100]].iter() {
+                let data = [*prefix, encoded, b"trailer"].concat();
+                let ours = explode_at(&data, prefix.len()).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+            }
+        }
+        let (encoded, _) = EXAMPLES[0];
+        assert_eq!(
+            explode_at(encoded, encoded.len()),
+            Err(Error::IncompleteInput)
+        );
+        assert_eq!(explode_at(encoded, 1000000), Err(Error::IncompleteInput));
+        assert_eq!(
+            explode_at(b"\x00\x00\x07", 1),
+            Err(Error::BadDictionary(7))
+        );
+    }
+
+    #[test]
+    fn explode_decompressed_len() {
+        for (encoded, decoded) in EXAMPLES {
+            let len = decompressed_len(encoded).unwrap();
+            assert_eq!(len, decoded.len() as u64);
+            let cut = &encoded[..encoded.len() - 1];
+            assert_eq!(decompressed_len(cut), Err(Error::IncompleteInput));
+        }
+        assert_eq!(
+            decompressed_len(&[0x00, 0x07]),
+            Err(Error::BadDictionary(7))
+        );
+    }
+
+    #[test]
+    fn explode_members() {
+        let mut data = vec![];
+        let mut ranges = vec![];
+        for (encoded, _) in EXAMPLES {
+            ranges.push(data.len()..data.len() + encoded.len());
+            data.extend_from_slice(encoded);
+        }
+        data.extend_from_slice(&[0; 7]);
+
+        let ours: Vec<_> = members(&data).collect::<Result<_, _>>().unwrap();
+        assert_eq!(ours.len(), EXAMPLES.len());
+        for (i, member) in ours.iter().enumerate() {
+            let (encoded, decoded) = EXAMPLES[i];
+            assert_eq!(decoded, &member.output[..]);
+            assert_eq!(member.header.dict_size, encoded[1]);
+            assert_eq!(member.input_range, ranges[i]);
+        }
+
+        // nothing, or only padding
+        assert_eq!(members(&[]).count(), 0);
+        assert_eq!(members(&[0xff, 0x00]).count(), 0);
+
+        // a bad member is an error, then the end
+        let (encoded, _) = EXAMPLES[0];
+        let mut data = encoded.to_vec();
+        data.extend_from_slice(&encoded[..encoded.len() - 1]);
+        let results: Vec<_> = members(&data).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(Error::IncompleteInput)));
     }
 }
@@ -0,0 +1,611 @@
+use super::codes::CanonicalHuffman;
+use super::{tables, Result};
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::io::Write;
+
+/// How literals are stored in an implode stream.
+///
+/// This is the first header byte. Coded literals are usually better
+/// for text, and uncoded literals for binary data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiteralMode {
+    /// Literals are stored as plain bytes.
+    Uncoded,
+    /// Literals are Huffman coded, using the fixed literal table.
+    Coded,
+}
+
+/// The dictionary size of an implode stream.
+///
+/// This is the second header byte. Larger dictionaries let copies
+/// reach further back, at the cost of more bits per copy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DictSize {
+    /// A 1024-byte dictionary.
+    Size1K,
+    /// A 2048-byte dictionary.
+    Size2K,
+    /// A 4096-byte dictionary.
+    Size4K,
+    /// Whichever of the sizes above gives the smallest output.
+    ///
+    /// [`implode`](fn.implode.html) tries each of them, and writes the
+    /// one it picks to the header. This takes about three times as
+    /// long. [`ImplodeWriter`](struct.ImplodeWriter.html) cannot see
+    /// all of its input before it starts, so it uses `Size4K`.
+    Auto,
+}
+
+impl DictSize {
+    /// The number of extra bits in distances, as stored in the
+    /// header: 4, 5, or 6. For `Auto`, this is the largest.
+    pub fn bits(self) -> u8 {
+        match self {
+            DictSize::Size1K => 4,
+            DictSize::Size2K => 5,
+            DictSize::Size4K | DictSize::Auto => 6,
+        }
+    }
+
+    /// The size of the dictionary in bytes. For `Auto`, this is the
+    /// largest.
+    pub fn window_size(self) -> usize {
+        64 << self.bits()
+    }
+}
+
+// the longest copy, as 519 is the end code
+const MAX_LEN: usize = 518;
+// the shortest copy we look for
+const MIN_LEN: usize = 3;
+// how many earlier positions to try for each match
+const MAX_CHAIN: usize = 64;
+// bits in the hash of the next three bytes
+const HASH_BITS: usize = 15;
+// enough to hold positions for the largest dictionary
+const HISTORY: usize = 4096;
+
+// a code ready to write with bits(), as (value, length)
+type Code = (u32, u8);
+
+// turn a table into codes indexed by symbol. codes in this format are
+// inverted and written most significant bit first, so do that here
+fn codes(table: &CanonicalHuffman<&[u8]>, symbols: usize) -> Vec<Code> {
+    (0..symbols)
+        .map(|symbol| {
+            let (code, len) = table.encode(symbol as u8).unwrap();
+            let mut value = 0;
+            for i in 0..len {
+                value |= (!(code >> (len - i - 1)) & 1) << i;
+            }
+            (value, len as u8)
+        })
+        .collect()
+}
+
+// implode compression state, shared by implode and ImplodeWriter
+#[derive(Debug)]
+pub(crate) struct Compressor {
+    lit: LiteralMode,
+    dict: DictSize,
+    literal: Vec<Code>,
+    length: Vec<Code>,
+    distance: Vec<Code>,
+
+    // compressed output, not yet taken
+    pub(crate) out: Vec<u8>,
+    bitbuf: u32,
+    bitcount: u8,
+
+    // input, with at least HISTORY bytes kept before pos
+    buf: Vec<u8>,
+    // position of buf[0] in the whole input, and of the next byte
+    // to compress
+    base: usize,
+    pos: usize,
+
+    // hash chains: head holds the last position + 1 with each hash,
+    // and prev holds the position + 1 before that, for each position
+    head: Vec<usize>,
+    prev: Vec<usize>,
+}
+
+impl Compressor {
+    pub(crate) fn new(lit: LiteralMode, dict: DictSize) -> Self {
+        let mut c = Compressor {
+            lit,
+            dict,
+            literal: codes(&tables::LITERAL, 256),
+            length: codes(&tables::LENGTH, 16),
+            distance: codes(&tables::DISTANCE, 64),
+            out: vec![],
+            bitbuf: 0,
+            bitcount: 0,
+            buf: vec![],
+            base: 0,
+            pos: 0,
+            head: vec![0; 1 << HASH_BITS],
+            prev: vec![0; HISTORY],
+        };
+        c.bits((lit == LiteralMode::Coded) as u32, 8);
+        c.bits(dict.bits() as u32, 8);
+        c
+    }
+
+    // add input, and compress as much as can be without knowing what
+    // comes next
+    pub(crate) fn write(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+        self.compress(false);
+    }
+
+    // compress the rest of the input, and end the stream
+    pub(crate) fn finish(&mut self) {
+        self.compress(true);
+        self.bits(1, 1);
+        self.code(self.length[15]);
+        self.bits(0xff, 8);
+        if self.bitcount > 0 {
+            self.out.push(self.bitbuf as u8);
+            self.bitbuf = 0;
+            self.bitcount = 0;
+        }
+    }
+
+    fn bits(&mut self, value: u32, n: u8) {
+        self.bitbuf |= (value & ((1 << n) - 1)) << self.bitcount;
+        self.bitcount += n;
+        while self.bitcount >= 8 {
+            self.out.push(self.bitbuf as u8);
+            self.bitbuf >>= 8;
+            self.bitcount -= 8;
+        }
+    }
+
+    fn code(&mut self, code: Code) {
+        self.bits(code.0, code.1);
+    }
+
+    fn hash(&self, pos: usize) -> usize {
+        let b = &self.buf[pos - self.base..];
+        let h = (b[0] as usize) << 10 ^ (b[1] as usize) << 5 ^ b[2] as usize;
+        h & ((1 << HASH_BITS) - 1)
+    }
+
+    // remember pos in the hash chains, if there are enough bytes
+    fn insert(&mut self, pos: usize) {
+        if pos + MIN_LEN > self.base + self.buf.len() {
+            return;
+        }
+        let h = self.hash(pos);
+        self.prev[pos % HISTORY] = self.head[h];
+        self.head[h] = pos + 1;
+    }
+
+    // find the longest earlier match for pos, as (len, dist)
+    fn find_match(&self) -> (usize, usize) {
+        let end = self.base + self.buf.len();
+        let max_len = core::cmp::min(MAX_LEN, end - self.pos);
+        if max_len < MIN_LEN {
+            return (0, 0);
+        }
+
+        let here = &self.buf[self.pos - self.base..][..max_len];
+        let mut best = (0, 0);
+        let mut candidate = self.head[self.hash(self.pos)];
+        for _ in 0..MAX_CHAIN {
+            if candidate == 0 {
+                break;
+            }
+            let p = candidate - 1;
+            let dist = self.pos - p;
+            if dist > self.dict.window_size() {
+                break;
+            }
+
+            let there = &self.buf[p - self.base..];
+            let len =
+                here.iter().zip(there).take_while(|(a, b)| a == b).count();
+            if len > best.0 {
+                best = (len, dist);
+                if len == max_len {
+                    break;
+                }
+            }
+
+            // entries are overwritten as positions wrap around, so
+            // stop if the chain no longer goes backwards
+            candidate = self.prev[p % HISTORY];
+            if candidate > p {
+                break;
+            }
+        }
+        best
+    }
+
+    fn literal(&mut self, value: u8) {
+        self.bits(0, 1);
+        match self.lit {
+            LiteralMode::Uncoded => self.bits(value as u32, 8),
+            LiteralMode::Coded => self.code(self.literal[value as usize]),
+        }
+    }
+
+    fn copy(&mut self, len: usize, dist: usize) {
+        let symbol = (0..16)
+            .find(|s| {
+                let base = tables::LEN_BASE[*s];
+                base <= len && len < base + (1 << tables::LEN_EXTRA[*s])
+            })
+            .unwrap();
+        self.bits(1, 1);
+        self.code(self.length[symbol]);
+        let extra = tables::LEN_EXTRA[symbol];
+        self.bits((len - tables::LEN_BASE[symbol]) as u32, extra);
+
+        let extra = if len == 2 { 2 } else { self.dict.bits() };
+        self.code(self.distance[(dist - 1) >> extra]);
+        self.bits((dist - 1) as u32, extra);
+    }
+
+    // compress input, leaving room to find the longest match unless
+    // this is the end
+    fn compress(&mut self, last: bool) {
+        let end = self.base + self.buf.len();
+        while self.pos < end && (last || end - self.pos >= MAX_LEN) {
+            let (len, dist) = self.find_match();
+            if len >= MIN_LEN {
+                self.copy(len, dist);
+                for i in 0..len {
+                    self.insert(self.pos + i);
+                }
+                self.pos += len;
+            } else {
+                self.literal(self.buf[self.pos - self.base]);
+                self.insert(self.pos);
+                self.pos += 1;
+            }
+        }
+
+        // drop input that is too far back to copy from
+        if self.pos - self.base > 2 * HISTORY {
+            let drop = self.pos - self.base - HISTORY;
+            self.buf.drain(..drop);
+            self.base += drop;
+        }
+    }
+}
+
+/// Compress a block of `data` in memory.
+///
+/// `lit` and `dict` choose the two header bytes, and the result can
+/// be decompressed with [`explode`](fn.explode.html). With
+/// [`DictSize::Auto`](enum.DictSize.html#variant.Auto), every
+/// dictionary size is tried and the smallest result is kept. This does
+/// not currently fail, but may in the future.
+///
+/// ```
+/// # fn main() -> explode::Result<()> {
+/// use explode::{DictSize, LiteralMode};
+///
+/// let data = "AIAIAIAIAIAIA".as_bytes();
+/// let compressed =
+///     explode::implode(data, LiteralMode::Uncoded, DictSize::Size1K)?;
+/// assert_eq!(explode::explode(&compressed)?, data);
+/// # Ok(()) }
+/// ```
+pub fn implode(
+    data: &[u8],
+    lit: LiteralMode,
+    dict: DictSize,
+) -> Result<Vec<u8>> {
+    if dict == DictSize::Auto {
+        // ties go to the smaller dictionary, which is cheaper to explode
+        let sizes = [DictSize::Size1K, DictSize::Size2K, DictSize::Size4K];
+        let all = sizes
+            .iter()
+            .map(|&dict| implode(data, lit, dict))
+            .collect::<Result<Vec<_>>>()?;
+        return Ok(all.into_iter().min_by_key(|out| out.len()).unwrap());
+    }
+
+    let mut c = Compressor::new(lit, dict);
+    c.write(data);
+    c.finish();
+    Ok(c.out)
+}
+
+/// A [`Write`][Write] wrapper that compresses.
+///
+///  [Write]: https://doc.rust-lang.org/std/io/trait.Write.html
+///
+/// Everything written here is compressed and written to the inner
+/// writer, as it becomes ready. Up to about 8K of input is kept, to
+/// find matches in and to look ahead. When done, call
+/// [`finish`](#method.finish) to write the end of the stream. Without
+/// it, the stream is incomplete.
+///
+/// This is only available with the `std` feature.
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use explode::{DictSize, ImplodeWriter, LiteralMode};
+/// use std::io::Write;
+///
+/// let mut w = ImplodeWriter::new(vec![], LiteralMode::Coded, DictSize::Size4K);
+/// w.write_all(b"AIAIAIAIAIAIA")?;
+/// let compressed = w.finish()?;
+/// # assert_eq!(explode::explode(&compressed).unwrap(), b"AIAIAIAIAIAIA");
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ImplodeWriter<W> {
+    inner: W,
+    c: Compressor,
+}
+
+#[cfg(feature = "std")]
+impl<W> ImplodeWriter<W>
+where
+    W: Write,
+{
+    /// Create a new compression wrapper around `inner`, using the
+    /// given header. `DictSize::Auto` is the same as `Size4K` here.
+    pub fn new(inner: W, lit: LiteralMode, dict: DictSize) -> Self {
+        ImplodeWriter {
+            inner,
+            c: Compressor::new(lit, dict),
+        }
+    }
+
+    /// Compress anything left, write the end code, and return the
+    /// inner writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.c.finish();
+        self.dump()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    // write out any compressed output. on error, whatever was not
+    // written is kept for next time
+    fn dump(&mut self) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind};
+        while !self.c.out.is_empty() {
+            match self.inner.write(&self.c.out) {
+                Ok(0) => {
+                    return Err(Error::new(
+                        ErrorKind::WriteZero,
+                        "failed to write compressed data",
+                    ))
+                }
+                Ok(n) => {
+                    self.c.out.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W> Write for ImplodeWriter<W>
+where
+    W: Write,
+{
+    /// Compress `buf`, and write out what is ready.
+    ///
+    /// Output left over from an earlier call is written first, and an
+    /// error there is returned before any of `buf` is taken. Once
+    /// `buf` is taken, an error writing its output only leaves that
+    /// output for the next call, which will return the error if it
+    /// happens again.
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.dump()?;
+        self.c.write(buf);
+        let _ = self.dump();
+        Ok(buf.len())
+    }
+
+    /// Write out all complete compressed bytes, and flush the inner
+    /// writer.
+    ///
+    /// Input that is still needed to look for matches, and the last
+    /// few bits of output, are kept until more is written or until
+    /// [`finish`](#method.finish).
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.dump()?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use super::ImplodeWriter;
+    use super::{implode, DictSize, LiteralMode};
+    use crate::examples::EXAMPLES;
+    use crate::explode;
+    #[cfg(feature = "std")]
+    use crate::ExplodeReader;
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use std::io::{Read, Write};
+
+    const MODES: &[LiteralMode] = &[LiteralMode::Uncoded, LiteralMode::Coded];
+    const SIZES: &[DictSize] =
+        &[DictSize::Size1K, DictSize::Size2K, DictSize::Size4K];
+
+    #[test]
+    fn implode_round_trip() {
+        for (_, decoded) in EXAMPLES {
+            for lit in MODES.iter() {
+                for dict in SIZES.iter() {
+                    let encoded = implode(decoded, *lit, *dict).unwrap();
+                    assert_eq!(
+                        encoded[0],
+                        (*lit == LiteralMode::Coded) as u8
+                    );
+                    assert_eq!(encoded[1], dict.bits());
+                    assert_eq!(*decoded, &explode(&encoded).unwrap()[..]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn implode_auto() {
+        for (_, decoded) in EXAMPLES {
+            for lit in MODES.iter() {
+                let sizes: Vec<usize> = SIZES
+                    .iter()
+                    .map(|d| implode(decoded, *lit, *d).unwrap().len())
+                    .collect();
+                let encoded = implode(decoded, *lit, DictSize::Auto).unwrap();
+                assert!(encoded.len() <= *sizes.iter().max().unwrap());
+                assert_eq!(encoded.len(), *sizes.iter().min().unwrap());
+                assert!((4..=6).contains(&encoded[1]));
+                assert_eq!(*decoded, &explode(&encoded).unwrap()[..]);
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn implode_auto_writer() {
+        // the writer can't choose, so takes the largest
+        let mut w = ImplodeWriter::new(vec![], MODES[0], DictSize::Auto);
+        w.write_all(b"AIAIAIAIAIAIA").unwrap();
+        assert_eq!(w.finish().unwrap()[1], 6);
+    }
+
+    #[test]
+    fn implode_compresses() {
+        let (encoded, decoded) = EXAMPLES[2];
+        let ours =
+            implode(decoded, LiteralMode::Coded, DictSize::Size4K).unwrap();
+        assert!(ours.len() < decoded.len() / 2);
+        // within reason of PKWARE's own output
+        assert!(ours.len() < encoded.len() * 11 / 10);
+    }
+
+    #[test]
+    fn implode_edge_cases() {
+        for lit in MODES.iter() {
+            for dict in SIZES.iter() {
+                // noise, repeated exactly one dictionary later
+                let mut x: u32 = 1;
+                let mut data: Vec<u8> = (0..dict.window_size())
+                    .map(|_| {
+                        x = x.wrapping_mul(1103515245).wrapping_add(12345);
+                        (x >> 16) as u8
+                    })
+                    .collect();
+                let noise = implode(&data, *lit, *dict).unwrap();
+                data.extend_from_within(..);
+                // long runs need the longest copies, and overlap
+                data.extend_from_slice(&[b'a'; 2000]);
+
+                let encoded = implode(&data, *lit, *dict).unwrap();
+                assert_eq!(explode(&encoded).unwrap(), data);
+                // the rest is only a few long copies
+                assert!(encoded.len() < noise.len() + 50);
+
+                for len in 0..4 {
+                    let encoded = implode(&data[..len], *lit, *dict).unwrap();
+                    assert_eq!(explode(&encoded).unwrap(), &data[..len]);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn implode_writer() {
+        for (_, decoded) in EXAMPLES {
+            for lit in MODES.iter() {
+                for dict in SIZES.iter() {
+                    let expected = implode(decoded, *lit, *dict).unwrap();
+                    for size in [1, 7, 4096].iter() {
+                        let mut w = ImplodeWriter::new(vec![], *lit, *dict);
+                        for chunk in decoded.chunks(*size) {
+                            w.write_all(chunk).unwrap();
+                            w.flush().unwrap();
+                        }
+                        let encoded = w.finish().unwrap();
+                        assert_eq!(expected, encoded);
+
+                        let mut r = ExplodeReader::new(&encoded[..]);
+                        let mut ours = vec![];
+                        r.read_to_end(&mut ours).unwrap();
+                        assert_eq!(*decoded, &ours[..]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn implode_writer_errors() {
+        use std::io::{Error, ErrorKind};
+
+        // a sink that takes a few bytes at a time, and while flaky,
+        // fails every other call
+        struct Flaky {
+            out: Vec<u8>,
+            failed: bool,
+            flaky: bool,
+        }
+        impl Write for Flaky {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.failed = self.flaky && !self.failed;
+                if self.failed {
+                    return Err(Error::other("oops"));
+                }
+                let n = buf.len().min(3);
+                self.out.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (_, decoded) = EXAMPLES[2];
+        let (lit, dict) = (LiteralMode::Coded, DictSize::Size4K);
+        let expected = implode(decoded, lit, dict).unwrap();
+        let flaky = Flaky {
+            out: vec![],
+            failed: false,
+            flaky: true,
+        };
+        let mut w = ImplodeWriter::new(flaky, lit, dict);
+        let mut errors = 0;
+        for mut chunk in decoded.chunks(100) {
+            // an error never takes any input, and taken input is
+            // never lost
+            while !chunk.is_empty() {
+                match w.write(chunk) {
+                    Ok(n) => {
+                        assert_eq!(n, chunk.len());
+                        chunk = &chunk[n..];
+                    }
+                    Err(e) => {
+                        assert_eq!(e.kind(), ErrorKind::Other);
+                        errors += 1;
+                    }
+                }
+            }
+        }
+        assert!(errors > 0);
+        while w.flush().is_err() {}
+        w.inner.flaky = false;
+        assert_eq!(expected, w.finish().unwrap().out);
+    }
+}
@@ -26,6 +26,7 @@
 //!  [File]: https://doc.rust-lang.org/std/io/struct.File.html
 //!
 //! ```
+//! # #[cfg(feature = "std")]
 //! # fn main() -> explode::Result<()> {
 //! # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
 //! # let some_file = std::io::Cursor::new(&bytes);
@@ -36,20 +37,138 @@
 //! // or other functions from Read
 //! # assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
 //! # Ok(()) }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
 //! ```
 //!
+//! To decompress data as it is written to a [`Write`][Write], use
+//! [`ExplodeWriter`](struct.ExplodeWriter.html).
+//!
 //! For more complicated uses that do not fit into these categories,
 //! use [`Explode`](struct.Explode.html).
+//!
+//! To compress, use [`implode`](fn.implode.html), or
+//! [`ImplodeWriter`](struct.ImplodeWriter.html) to compress into a
+//! [`Write`][Write]. The result can be decompressed with any of the
+//! above.
+//!
+//!  [Write]: https://doc.rust-lang.org/std/io/trait.Write.html
+//!
+//! ```
+//! # fn main() -> explode::Result<()> {
+//! use explode::{DictSize, LiteralMode};
+//!
+//! let data = "AIAIAIAIAIAIA".as_bytes();
+//! let compressed =
+//!     explode::implode(data, LiteralMode::Uncoded, DictSize::Size1K)?;
+//! assert_eq!(explode::explode(&compressed)?, data);
+//! # Ok(()) }
+//! ```
+//!
+//! PKZIP's *imploding* compression method, used in ZIP archives, is a
+//! different format. It is handled separately in the
+//! [`pkzip`](pkzip/index.html) module.
+//!
+//! The fixed Huffman tables used by every implode stream are in the
+//! [`tables`](tables/index.html) module.
+//!
+//! # Features
+//!
+//! * `bytes` - decompress into [`Bytes`][bytes] with
+//!   [`explode_to_bytes`](fn.explode_to_bytes.html), or between
+//!   `Buf` and `BufMut` with [`explode_buf`](fn.explode_buf.html).
+//! * `crc` - compute the CRC-32 of decompressed output with
+//!   [`Crc32`](struct.Crc32.html), or
+//!   [`ExplodeReader::with_crc32`](struct.ExplodeReader.html#method.with_crc32).
+//! * `debug-checks` - expose
+//!   [`Explode::check_invariants`](struct.Explode.html#method.check_invariants),
+//!   for testing and fuzzing.
+//! * `futures` - decompress a [`Stream`][stream] of
+//!   [`Bytes`][bytes] chunks with
+//!   [`explode_stream`](fn.explode_stream.html), or a futures
+//!   [`AsyncRead`][futuresread] with
+//!   [`AsyncExplodeReader`](struct.AsyncExplodeReader.html). This
+//!   also enables `bytes`.
+//! * `nightly` - implement [`Read::read_buf`][read_buf] on
+//!   [`ExplodeReader`](struct.ExplodeReader.html), so reading into
+//!   uninitialized buffers does not need to zero them first. This
+//!   requires a nightly compiler.
+//! * `std` - on by default. Without this, the crate is `no_std` and
+//!   needs only [`alloc`][alloc], but everything that uses
+//!   [`std::io`][io] is unavailable, such as
+//!   [`ExplodeReader`](struct.ExplodeReader.html) and
+//!   [`Error::IO`](enum.Error.html#variant.IO). `futures`,
+//!   `nightly`, and `tokio` also enable this.
+//! * `tokio` - decompress a tokio [`AsyncRead`][asyncread] with
+//!   [`AsyncExplodeReader`](struct.AsyncExplodeReader.html).
+//! * `tracing` - emit [`tracing`][tracing] events while decompressing,
+//!   such as when the header is parsed, after every MiB of output, or
+//!   when an error occurs. The one-shot functions like
+//!   [`explode`](fn.explode.html) also enter a span.
+//!
+//!  [alloc]: https://doc.rust-lang.org/alloc/
+//!  [io]: https://doc.rust-lang.org/std/io/
+//!  [bytes]: https://docs.rs/bytes
+//!  [stream]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
+//!  [read_buf]: https://doc.rust-lang.org/std/io/trait.Read.html#method.read_buf
+//!  [futuresread]: https://docs.rs/futures/0.3/futures/io/trait.AsyncRead.html
+//!  [asyncread]: https://docs.rs/tokio/1/tokio/io/trait.AsyncRead.html
+//!  [tracing]: https://docs.rs/tracing
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(read_buf, core_io_borrowed_buf))]
+
+extern crate alloc;
+// tests always run with std, though the crate may not use it
+#[cfg(all(test, not(feature = "std")))]
+extern crate std;
+
+#[macro_use]
+mod trace;
 
+#[cfg(any(feature = "futures", feature = "tokio"))]
+mod async_reader;
+mod checksum;
 mod codes;
+#[cfg(test)]
+mod compat;
 mod error;
 mod examples;
 mod explode;
+mod implode;
+pub mod pkzip;
+#[cfg(feature = "std")]
 mod reader;
-mod tables;
+#[cfg(feature = "futures")]
+mod stream;
+pub mod tables;
+mod window;
+#[cfg(feature = "std")]
+mod writer;
 
 pub use self::explode::{
-    explode, explode_with_buffer, Explode, ExplodeBuffer,
+    decompressed_len, explode, explode_any, explode_at, explode_counted,
+    explode_into, explode_precise, explode_transcode, explode_with_buffer,
+    explode_with_config, members, Checkpoint, DecodeReport, EndReason,
+    Explode, ExplodeBuffer, ExplodeConfig, Header, Member, Phase, Status,
+    TransformOrder,
 };
+#[cfg(feature = "bytes")]
+pub use self::explode::{explode_buf, explode_to_bytes};
+#[cfg(feature = "std")]
+pub use self::explode::{explode_pooled, explode_to_writer};
+#[cfg(any(feature = "futures", feature = "tokio"))]
+pub use async_reader::AsyncExplodeReader;
+#[cfg(feature = "crc")]
+pub use checksum::Crc32;
+pub use checksum::{Checksum, NoChecksum};
 pub use error::{Error, Result};
-pub use reader::ExplodeReader;
+#[cfg(feature = "std")]
+pub use implode::ImplodeWriter;
+pub use implode::{implode, DictSize, LiteralMode};
+#[cfg(feature = "std")]
+pub use reader::{ExplodeBytes, ExplodeReader};
+#[cfg(feature = "futures")]
+pub use stream::{explode_stream, ExplodeStream};
+#[cfg(feature = "std")]
+pub use writer::ExplodeWriter;
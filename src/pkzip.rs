@@ -0,0 +1,275 @@
+//! Decompression for PKZIP's *imploding* method (method 6).
+//!
+//! This is a different format from the PKWARE Data Compression
+//! Library implode handled by the rest of this crate, though the two
+//! are often confused. ZIP members store their Huffman trees in the
+//! compressed data, and the dictionary size and literal tree are
+//! chosen by the general purpose bit flag in the ZIP header rather
+//! than by a header in the data.
+//!
+//! This follows `explode.c` from Info-ZIP's UnZip, and the
+//! description in PKWARE's [APPNOTE.TXT][appnote].
+//!
+//!  [appnote]: https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+//!
+//! ```
+//! # fn main() -> explode::Result<()> {
+//! # let member = include_bytes!("../tests/pkzip/abc-4k.bin");
+//! // compressed data, size and flags all come from the ZIP headers
+//! let (data, size, flags) = (&member[..], 10, 0);
+//! let result = explode::pkzip::explode(data, size, flags)?;
+//! assert_eq!(result, "abcabcabca".as_bytes());
+//! # Ok(()) }
+//! ```
+
+use crate::codes::{CanonicalHuffman, DecodeResult};
+use crate::{Error, Result};
+
+use alloc::vec::Vec;
+
+/// General purpose bit flag for an 8K dictionary, instead of 4K.
+pub const FLAG_8K_DICTIONARY: u16 = 1 << 1;
+
+/// General purpose bit flag for a literal tree. Without this,
+/// literals are stored as plain bytes.
+pub const FLAG_LITERAL_TREE: u16 = 1 << 2;
+
+// a Shannon-Fano tree, read from the compressed data
+enum Tree {
+    // every symbol has the same length, so the code is the symbol.
+    // this is kept separate as counts in CanonicalHuffman are u8,
+    // and a flat 256-symbol tree does not fit
+    Flat { len: usize, symbols: usize },
+    Coded(CanonicalHuffman<Vec<u8>>),
+}
+
+// read bits least significant first
+struct Bits<'a> {
+    data: &'a [u8],
+    bitbuf: u32,
+    bitcount: usize,
+}
+
+impl<'a> Bits<'a> {
+    fn bits(&mut self, n: usize) -> Result<u32> {
+        while self.bitcount < n {
+            let (b, rest) =
+                self.data.split_first().ok_or(Error::IncompleteInput)?;
+            self.bitbuf |= (*b as u32) << self.bitcount;
+            self.bitcount += 8;
+            self.data = rest;
+        }
+        let v = self.bitbuf & ((1 << n) - 1);
+        self.bitbuf >>= n;
+        self.bitcount -= n;
+        Ok(v)
+    }
+
+    // codes are stored inverted, most significant bit first
+    fn decode(&mut self, tree: &Tree) -> Result<usize> {
+        match tree {
+            Tree::Flat { len, symbols } => {
+                let mut symbol = 0;
+                for _ in 0..*len {
+                    symbol = (symbol << 1) | (self.bits(1)? ^ 1) as usize;
+                }
+                // codes past the last symbol are unused
+                if symbol >= *symbols {
+                    return Err(Error::BadTree);
+                }
+                Ok(symbol)
+            }
+            Tree::Coded(codebook) => {
+                let mut decoder = codebook.decoder();
+                loop {
+                    match decoder.feed(self.bits(1)? == 0) {
+                        DecodeResult::Incomplete => continue,
+                        DecodeResult::Invalid => return Err(Error::BadTree),
+                        DecodeResult::Ok(symbol) => {
+                            return Ok(symbol as usize)
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// read a tree of `symbols` symbols from the start of data, and return
+// it with the rest of the data
+fn read_tree(data: &[u8], symbols: usize) -> Result<(Tree, &[u8])> {
+    // one byte for the number of bytes that follow, minus one, then
+    // bytes of 0xHL, for H + 1 symbols of length L + 1
+    let (n, data) = data.split_first().ok_or(Error::IncompleteInput)?;
+    let n = *n as usize + 1;
+    if data.len() < n {
+        return Err(Error::IncompleteInput);
+    }
+    let (packed, data) = data.split_at(n);
+
+    let mut lengths = Vec::with_capacity(symbols);
+    for b in packed.iter() {
+        let len = (*b & 0b1111) + 1;
+        let count = (*b >> 4) as usize + 1;
+        for _ in 0..count {
+            lengths.push(len);
+        }
+    }
+    if lengths.len() != symbols {
+        return Err(Error::BadTree);
+    }
+
+    if lengths.iter().all(|len| *len == lengths[0]) {
+        // flat trees are only valid if they have room for every symbol
+        let len = lengths[0] as usize;
+        if symbols > 1 << len {
+            return Err(Error::BadTree);
+        }
+        return Ok((Tree::Flat { len, symbols }, data));
+    }
+    let tree =
+        CanonicalHuffman::new_from_lengths(&lengths).ok_or(Error::BadTree)?;
+    Ok((Tree::Coded(tree), data))
+}
+
+/// Decompress the data of a ZIP member stored with method 6,
+/// *imploding*.
+///
+/// `uncompressed_size` is the uncompressed size from the ZIP header,
+/// and decompression stops once this many bytes are produced. `bits`
+/// is the general purpose bit flag from the ZIP header, which chooses
+/// the dictionary size and whether literals are coded. Only
+/// [`FLAG_8K_DICTIONARY`](constant.FLAG_8K_DICTIONARY.html) and
+/// [`FLAG_LITERAL_TREE`](constant.FLAG_LITERAL_TREE.html) are used,
+/// and any other bits are ignored.
+///
+/// Returns [`Error::BadTree`](../enum.Error.html#variant.BadTree) if
+/// a stored tree is invalid, or
+/// [`Error::IncompleteInput`](../enum.Error.html#variant.IncompleteInput)
+/// if `data` runs out before `uncompressed_size` bytes. As in PKZIP,
+/// copies from before the start of the output produce zeros.
+pub fn explode(
+    data: &[u8],
+    uncompressed_size: usize,
+    bits: u16,
+) -> Result<Vec<u8>> {
+    let dict_bits = if bits & FLAG_8K_DICTIONARY != 0 { 7 } else { 6 };
+    let (literal, data) = if bits & FLAG_LITERAL_TREE != 0 {
+        let (tree, data) = read_tree(data, 256)?;
+        (Some(tree), data)
+    } else {
+        (None, data)
+    };
+    let (length, data) = read_tree(data, 64)?;
+    let (distance, data) = read_tree(data, 64)?;
+    let min_len = if literal.is_some() { 3 } else { 2 };
+
+    let mut input = Bits {
+        data,
+        bitbuf: 0,
+        bitcount: 0,
+    };
+    // uncompressed_size comes from the archive, so don't trust it too far
+    let mut out = Vec::with_capacity(uncompressed_size.min(1 << 20));
+    while out.len() < uncompressed_size {
+        if input.bits(1)? == 1 {
+            // literal
+            let lit = match literal {
+                Some(ref tree) => input.decode(tree)? as u8,
+                None => input.bits(8)? as u8,
+            };
+            out.push(lit);
+        } else {
+            // copy, low bits of distance first
+            let low = input.bits(dict_bits)? as usize;
+            let high = input.decode(&distance)?;
+            let dist = ((high << dict_bits) | low) + 1;
+            let mut len = input.decode(&length)?;
+            if len == 63 {
+                len += input.bits(8)? as usize;
+            }
+            len += min_len;
+
+            let len = len.min(uncompressed_size - out.len());
+            for _ in 0..len {
+                let b = if dist > out.len() {
+                    0
+                } else {
+                    out[out.len() - dist]
+                };
+                out.push(b);
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{explode, FLAG_8K_DICTIONARY, FLAG_LITERAL_TREE};
+    use crate::Error;
+    use alloc::vec;
+
+    // a flat 64-symbol tree, with 6 bits per symbol
+    const FLAT: &[u8] = &[3, 0xf5, 0xf5, 0xf5, 0xf5];
+
+    #[test]
+    fn pkzip_abc() {
+        let member = include_bytes!("../tests/pkzip/abc-4k.bin");
+        assert_eq!(explode(member, 10, 0).unwrap(), b"abcabcabca");
+
+        // stops exactly at the size, even mid-copy
+        assert_eq!(explode(member, 5, 0).unwrap(), b"abcab");
+        assert_eq!(explode(member, 0, 0).unwrap(), b"");
+        match explode(member, 11, 0) {
+            Err(Error::IncompleteInput) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pkzip_bad_trees() {
+        // too few symbols
+        let data = [0, 0x05];
+        assert!(matches!(explode(&data, 1, 0), Err(Error::BadTree)));
+        let flags = FLAG_LITERAL_TREE;
+        assert!(matches!(explode(FLAT, 1, flags), Err(Error::BadTree)));
+
+        // flat, but too short for every symbol
+        let data = [3, 0xf4, 0xf4, 0xf4, 0xf4];
+        assert!(matches!(explode(&data, 1, 0), Err(Error::BadTree)));
+
+        // oversubscribed
+        let mut data = vec![3, 0xf0, 0xf5, 0xf5, 0xf5];
+        data.extend_from_slice(FLAT);
+        assert!(matches!(explode(&data, 1, 0), Err(Error::BadTree)));
+
+        // flat, but longer than needed, so some codes are unused. a
+        // length code of 1111111 is for symbol 127 of 64
+        let mut data = vec![3, 0xf6, 0xf6, 0xf6, 0xf6];
+        data.extend_from_slice(FLAT);
+        // flag 0, low distance bits 0, then inverted codes 0 and 127
+        data.extend_from_slice(&[0b1000_0000, 0b0001_1111, 0b0000_0000]);
+        assert!(matches!(explode(&data, 2, 0), Err(Error::BadTree)));
+
+        // truncated tree
+        assert!(matches!(
+            explode(&FLAT[..3], 1, 0),
+            Err(Error::IncompleteInput)
+        ));
+    }
+
+    #[test]
+    fn pkzip_before_start() {
+        // a copy of distance 1 and length 2, with nothing before it
+        let mut data = FLAT.to_vec();
+        data.extend_from_slice(FLAT);
+        // flag 0, low distance bits 0, then inverted codes 0 and 0
+        let mut small = data.clone();
+        small.extend_from_slice(&[0b1000_0000, 0b1111_1111, 0b0000_0111]);
+        assert_eq!(explode(&small, 2, 0).unwrap(), [0, 0]);
+        // one more low distance bit with an 8K dictionary
+        data.extend_from_slice(&[0b0000_0000, 0b1111_1111, 0b0000_1111]);
+        assert_eq!(explode(&data, 2, FLAG_8K_DICTIONARY).unwrap(), [0, 0]);
+    }
+}
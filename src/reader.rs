@@ -1,6 +1,17 @@
-use crate::{Error, Explode};
+use crate::explode::{as_uninit, assume_init};
+#[cfg(feature = "crc")]
+use crate::Crc32;
+use crate::{Checksum, EndReason, Error, Explode, Header, NoChecksum};
 
-use std::io::{Error as IOError, ErrorKind, Read, Result};
+use std::io::{
+    BufRead, BufReader, Error as IOError, ErrorKind, Read, Result, Seek,
+    SeekFrom,
+};
+use std::mem::MaybeUninit;
+use std::task::Poll;
+
+#[cfg(feature = "nightly")]
+use std::io::BorrowedCursor;
 
 /// A [`Read`][Read] wrapper that decompresses.
 ///
@@ -24,69 +35,915 @@ use std::io::{Error as IOError, ErrorKind, Read, Result};
 /// # assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
 /// # Ok(()) }
 /// ```
-pub struct ExplodeReader<R> {
-    inner: R,
+///
+/// Input is read from `inner` only as it is needed, so nothing past
+/// the end of the compressed stream is read, and `inner` is left
+/// right after it. This reads one byte at a time, which is slow for
+/// unbuffered readers like a [`File`][File]. For those, use
+/// [`buffered`](#method.buffered) to read input in large blocks,
+/// with any input read past the end of the compressed stream in
+/// [`buffer`](#method.buffer). If `inner` is already buffered, use
+/// [`from_bufread`](#method.from_bufread) instead to read directly
+/// from its buffer.
+///
+///  [BufReader]: https://doc.rust-lang.org/std/io/struct.BufReader.html
+///
+/// To compute a checksum of the decompressed data as it is read, use
+/// [`with_checksum`](#method.with_checksum). To read several streams
+/// stored back to back as one, use
+/// [`multi_member`](#method.multi_member).
+#[derive(Debug)]
+pub struct ExplodeReader<R, C = NoChecksum> {
+    inner: Input<R>,
+    state: ReadState,
+    checksum: C,
+}
+
+// decompression state shared by ExplodeReader and AsyncExplodeReader
+#[derive(Debug)]
+pub(crate) struct ReadState {
     dec: Explode,
-    leftover: Option<u8>,
+    // bytes consumed from inner since the stream started, for rewind
+    consumed: u64,
+    // continue with the next stream after the end of one
+    pub(crate) multi_member: bool,
+    // streams finished so far
+    pub(crate) members: u64,
+}
+
+// the input for ExplodeReader, which is either read a byte at a time,
+// buffered here, or already buffered. fill and consume are from
+// BufRead on R
+enum Input<R> {
+    Unbuffered {
+        inner: R,
+        // a byte read from inner but not yet consumed
+        byte: Option<u8>,
+    },
+    Buffered(BufReader<R>),
+    Direct {
+        inner: R,
+        fill: fn(&mut R) -> Result<&[u8]>,
+        consume: fn(&mut R, usize),
+    },
 }
 
+// how much input to read from inner at once
+const INPUT_SIZE: usize = 8192;
+
 impl<R> ExplodeReader<R>
 where
     R: Read,
 {
     /// Create a new decompression wrapper around `inner`.
     pub fn new(inner: R) -> Self {
+        Self::with_explode(inner, Explode::new())
+    }
+
+    /// Create a new decompression wrapper around `inner`, which reads
+    /// input ahead in large blocks.
+    ///
+    /// This is much faster than [`new`](#method.new) for unbuffered
+    /// readers, so there is no need to wrap `inner` in a
+    /// [`BufReader`][BufReader]. Input may be read past the end of the
+    /// compressed stream, and is then available from
+    /// [`buffer`](#method.buffer) or
+    /// [`into_parts`](#method.into_parts).
+    ///
+    ///  [BufReader]: https://doc.rust-lang.org/std/io/struct.BufReader.html
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::io::Read;
+    ///
+    /// let mut bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// bytes.extend_from_slice(b"trailer");
+    /// let mut reader = explode::ExplodeReader::buffered(&bytes[..]);
+    /// let mut decompressed = vec![];
+    /// reader.read_to_end(&mut decompressed)?;
+    /// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+    /// assert_eq!(reader.buffer(), b"trailer");
+    /// # Ok(()) }
+    /// ```
+    pub fn buffered(inner: R) -> Self {
         ExplodeReader {
-            inner,
-            dec: Explode::new(),
-            leftover: None,
+            inner: Input::Buffered(BufReader::with_capacity(
+                INPUT_SIZE, inner,
+            )),
+            state: ReadState::new(Explode::new()),
+            checksum: NoChecksum,
+        }
+    }
+
+    /// Create a new decompression wrapper around `inner`, continuing
+    /// from an existing decompression state `dec`.
+    ///
+    /// This is useful if `dec` has already been partially driven with
+    /// [`ExplodeBuffer::feed`](struct.ExplodeBuffer.html#method.feed).
+    /// The last call to `feed` must have returned
+    /// [`Error::IncompleteInput`](enum.Error.html#variant.IncompleteInput),
+    /// so that `dec` is ready for the next byte from `inner`.
+    pub fn with_explode(inner: R, dec: Explode) -> Self {
+        ExplodeReader {
+            inner: Input::Unbuffered { inner, byte: None },
+            state: ReadState::new(dec),
+            checksum: NoChecksum,
         }
     }
 }
 
-impl<R> Read for ExplodeReader<R>
+impl<R> ExplodeReader<R>
+where
+    R: BufRead,
+{
+    /// Create a new decompression wrapper around a buffered `inner`.
+    ///
+    /// Input is read straight from the buffer in `inner`, instead of
+    /// being copied into another buffer first. Only the input that
+    /// is needed is consumed, so once decompression is finished,
+    /// `inner` is positioned right after the compressed stream.
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// # let some_file = std::io::Cursor::new(&bytes);
+    /// use std::io::{BufReader, Read};
+    /// let buffered = BufReader::new(some_file);
+    /// let mut reader = explode::ExplodeReader::from_bufread(buffered);
+    /// let mut decompressed = vec![];
+    /// reader.read_to_end(&mut decompressed)?;
+    /// # assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+    /// # Ok(()) }
+    /// ```
+    pub fn from_bufread(inner: R) -> Self {
+        ExplodeReader {
+            inner: Input::Direct {
+                inner,
+                fill: R::fill_buf,
+                consume: R::consume,
+            },
+            state: ReadState::new(Explode::new()),
+            checksum: NoChecksum,
+        }
+    }
+}
+
+impl<R, C> ExplodeReader<R, C>
 where
     R: Read,
+    C: Checksum,
 {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        if self.dec.done() {
-            return Ok(0);
+    /// Compute a checksum of all decompressed data read from here on.
+    ///
+    /// Any previous checksum is discarded. Get the checksum back with
+    /// [`checksum`](#method.checksum) or
+    /// [`into_checksum`](#method.into_checksum).
+    pub fn with_checksum<D: Checksum>(
+        self,
+        checksum: D,
+    ) -> ExplodeReader<R, D> {
+        ExplodeReader {
+            inner: self.inner,
+            state: self.state,
+            checksum,
         }
+    }
 
-        let mut decbuf = self.dec.with_buffer(buf);
-        let mut byte = 0;
+    /// Read several compressed streams stored back to back, as if
+    /// their output was one stream.
+    ///
+    /// Once `multi` is true, at the end of one stream the decompressor
+    /// is [`reset`](#method.reset) and continues with the next stream
+    /// right after it, until `inner` runs out. The input must contain
+    /// nothing but whole streams, as anything after the last one is
+    /// decompressed as another stream. The number of streams finished
+    /// so far is [`member_count`](#method.member_count).
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::io::Read;
+    ///
+    /// let member = [0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// let bytes = [member, member].concat();
+    /// let mut reader =
+    ///     explode::ExplodeReader::new(&bytes[..]).multi_member(true);
+    /// let mut decompressed = vec![];
+    /// reader.read_to_end(&mut decompressed)?;
+    /// assert_eq!(decompressed, b"AIAIAIAIAIAIAAIAIAIAIAIAIA");
+    /// assert_eq!(reader.member_count(), 2);
+    /// # Ok(()) }
+    /// ```
+    pub fn multi_member(mut self, multi: bool) -> Self {
+        self.state.multi_member = multi;
+        self
+    }
+
+    /// Returns true if the stream was read to its end code.
+    ///
+    /// This is false until the end of the stream is reached. A reader
+    /// whose decompressor was configured with
+    /// [`ExplodeConfig::tolerant`](struct.ExplodeConfig.html#method.tolerant)
+    /// can also reach the end of a stream which has no end code,
+    /// without an error. This is false in that case, so it can be
+    /// used after [`read_to_end`][read_to_end] to check that the
+    /// whole stream was there.
+    ///
+    ///  [read_to_end]: https://doc.rust-lang.org/std/io/trait.Read.html#method.read_to_end
+    pub fn finished_cleanly(&self) -> bool {
+        self.state.dec.end_reason() == Some(EndReason::EndCode)
+    }
+
+    /// Returns the number of compressed streams finished so far.
+    ///
+    /// This is at most 1, unless
+    /// [`multi_member`](#method.multi_member) is used.
+    pub fn member_count(&self) -> u64 {
+        self.state.members
+    }
+
+    /// Compute the CRC-32 of all decompressed data read from here on.
+    ///
+    /// This is the same as [`with_checksum`](#method.with_checksum)
+    /// with a [`Crc32`](struct.Crc32.html), which documents the
+    /// polynomial used. Get the result with [`crc32`](#method.crc32).
+    ///
+    /// This is only available with the `crc` feature.
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// # let some_file = std::io::Cursor::new(&bytes);
+    /// use std::io::Read;
+    /// let reader = explode::ExplodeReader::new(some_file);
+    /// let mut reader = reader.with_crc32();
+    /// let mut decompressed = vec![];
+    /// reader.read_to_end(&mut decompressed)?;
+    /// assert_eq!(reader.crc32(), 0x506b8690);
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "crc")]
+    pub fn with_crc32(self) -> ExplodeReader<R, Crc32> {
+        self.with_checksum(Crc32::new())
+    }
+
+    /// Get the input that has been read from `inner`, but not yet
+    /// decompressed.
+    ///
+    /// Once decompression is finished, this holds any data that was
+    /// read from `inner` past the end of the compressed stream. Only
+    /// readers created with [`buffered`](#method.buffered) read ahead
+    /// like this. Other readers leave unused input in `inner`.
+    pub fn buffer(&self) -> &[u8] {
+        match self.inner {
+            Input::Unbuffered { ref byte, .. } => byte.as_slice(),
+            Input::Buffered(ref inner) => inner.buffer(),
+            Input::Direct { .. } => &[],
+        }
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.inner.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly will skip input that the decompressor
+    /// has not seen yet, and corrupt the output.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.inner.get_mut()
+    }
+
+    /// Discard this reader, and return the underlying reader.
+    ///
+    /// Readers created with [`buffered`](#method.buffered) read input
+    /// from `inner` ahead of time, so it may already be positioned past
+    /// the end of the compressed stream. Any input read but not used is
+    /// in [`buffer`](#method.buffer), and is lost here; use
+    /// [`into_parts`](#method.into_parts) to keep it. Other readers
+    /// leave `inner` positioned right after the input that was used.
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::io::{BufReader, Read};
+    ///
+    /// let mut bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// bytes.extend_from_slice(b"trailer");
+    /// let buffered = BufReader::new(&bytes[..]);
+    /// let mut reader = explode::ExplodeReader::from_bufread(buffered);
+    /// reader.read_to_end(&mut vec![])?;
+    ///
+    /// let mut rest = vec![];
+    /// reader.into_inner().read_to_end(&mut rest)?;
+    /// assert_eq!(rest, b"trailer");
+    /// # Ok(()) }
+    /// ```
+    pub fn into_inner(self) -> R {
+        match self.inner {
+            Input::Unbuffered { inner, .. } => inner,
+            Input::Buffered(inner) => inner.into_inner(),
+            Input::Direct { inner, .. } => inner,
+        }
+    }
+
+    /// Discard this reader, and return the underlying reader with any
+    /// input that was read from it but not used.
+    ///
+    /// Reading the returned bytes, and then the rest of `inner`,
+    /// continues right after the end of the compressed stream. This
+    /// is useful to parse data following the stream, such as another
+    /// stream.
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// use std::io::Read;
+    ///
+    /// let mut bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// bytes.extend_from_slice(b"trailer");
+    /// let mut reader = explode::ExplodeReader::buffered(&bytes[..]);
+    /// reader.read_to_end(&mut vec![])?;
+    ///
+    /// let (inner, unused) = reader.into_parts();
+    /// let mut rest = vec![];
+    /// (&unused[..]).chain(inner).read_to_end(&mut rest)?;
+    /// assert_eq!(rest, b"trailer");
+    /// # Ok(()) }
+    /// ```
+    pub fn into_parts(self) -> (R, Vec<u8>) {
+        let unused = self.buffer().to_vec();
+        (self.into_inner(), unused)
+    }
+
+    /// Reset the decompressor, to start a new stream.
+    ///
+    /// After this, the next read decodes a new stream from the current
+    /// position in the input, starting with any input already in
+    /// [`buffer`](#method.buffer). The decompressor is reset with
+    /// [`Explode::reset`](struct.Explode.html#method.reset), so its
+    /// configuration is kept, and no memory is reallocated. The
+    /// checksum is not reset.
+    pub fn reset(&mut self) {
+        self.state.reset();
+    }
+
+    /// Get a reference to the checksum.
+    pub fn checksum(&self) -> &C {
+        &self.checksum
+    }
+
+    /// Discard this reader, and return the checksum.
+    pub fn into_checksum(self) -> C {
+        self.checksum
+    }
+
+    /// Read and return the stream header, without producing output.
+    ///
+    /// This decompresses only as much input as is needed to parse
+    /// the header. Calling this is optional, and later reads will
+    /// decompress the whole stream as usual. If the header was
+    /// already parsed, or the stream is headerless, this reads
+    /// nothing.
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// # let some_file = std::io::Cursor::new(&bytes);
+    /// let mut reader = explode::ExplodeReader::new(some_file);
+    /// let header = reader.read_header()?;
+    /// assert!(!header.coded_literals);
+    /// assert_eq!(header.window_size(), 1024);
+    /// # Ok(()) }
+    /// ```
+    pub fn read_header(&mut self) -> Result<Header> {
         loop {
-            if let Some(v) = self.leftover {
-                byte = v;
-                self.leftover = None;
-            } else {
-                if self.inner.read(std::slice::from_mut(&mut byte))? == 0 {
-                    break;
+            if let Some(header) = self.state.dec.header() {
+                return Ok(header);
+            }
+
+            // without room for output, this stops after the header
+            let mut decbuf = self.state.dec.with_buffer(&mut []);
+            let mut byte = 0;
+            if decbuf.wants_input() {
+                match fill_buf(&mut self.inner)? {
+                    [] => {
+                        return Err(IOError::new(
+                            ErrorKind::UnexpectedEof,
+                            Error::IncompleteInput,
+                        ))
+                    }
+                    [b, ..] => byte = *b,
                 }
+                self.inner.consume(1);
+                self.state.consumed += 1;
             }
 
             match decbuf.feed(byte) {
-                Ok(()) => {
-                    self.leftover = Some(byte);
-                    return Ok(decbuf.len());
-                }
+                Ok(()) => (),
                 Err(Error::IncompleteInput) => continue,
                 Err(e) => {
                     return Err(IOError::new(ErrorKind::InvalidData, e))
                 }
             }
         }
-        Err(IOError::new(
-            ErrorKind::UnexpectedEof,
-            Error::IncompleteInput,
-        ))
+    }
+
+    /// Decompress and discard `n` bytes of output.
+    ///
+    /// Returns the number of bytes actually skipped, which is less
+    /// than `n` only if the end of the decompressed stream was
+    /// reached.
+    pub fn skip(&mut self, n: u64) -> Result<u64> {
+        let mut buf = [0; 4096];
+        let mut skipped = 0;
+        while skipped < n {
+            let amt = std::cmp::min(n - skipped, buf.len() as u64) as usize;
+            let len = self.read(&mut buf[..amt])?;
+            if len == 0 {
+                break;
+            }
+            skipped += len as u64;
+        }
+        Ok(skipped)
+    }
+
+    /// Iterate over the lines of the decompressed output.
+    ///
+    /// Unlike [`BufRead::lines`][lines], invalid UTF-8 does not cause
+    /// an error. Instead, it is replaced as in
+    /// [`String::from_utf8_lossy`][lossy]. Lines may end in either
+    /// `\n` or `\r\n`, and the line ending is not included.
+    ///
+    ///  [lines]: https://doc.rust-lang.org/std/io/trait.BufRead.html#method.lines
+    ///  [lossy]: https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy
+    pub fn lines_lossy(self) -> impl Iterator<Item = Result<String>> {
+        let mut reader = BufReader::new(self);
+        let mut line = Vec::new();
+        std::iter::from_fn(move || {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => None,
+                Ok(_) => {
+                    if line.last() == Some(&b'\n') {
+                        line.pop();
+                        if line.last() == Some(&b'\r') {
+                            line.pop();
+                        }
+                    }
+                    Some(Ok(String::from_utf8_lossy(&line).into_owned()))
+                }
+                Err(e) => Some(Err(e)),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "crc")]
+impl<R> ExplodeReader<R, Crc32> {
+    /// Get the CRC-32 of all decompressed data read so far.
+    ///
+    /// This is only available with the `crc` feature.
+    pub fn crc32(&self) -> u32 {
+        self.checksum.value()
+    }
+}
+
+impl<R, C> ExplodeReader<R, C>
+where
+    R: Read + Seek,
+    C: Checksum,
+{
+    /// Seek `inner` back to the start of the current stream, and
+    /// [`reset`](#method.reset), so the same stream can be read
+    /// again.
+    ///
+    /// The start is where this reader started reading from `inner`,
+    /// or where the last call to `reset` left it. For readers created
+    /// with [`with_explode`](#method.with_explode), this is not the
+    /// true start of the stream, so decompression will fail.
+    ///
+    /// ```
+    /// # fn main() -> std::io::Result<()> {
+    /// # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+    /// # let some_file = std::io::Cursor::new(&bytes);
+    /// use std::io::Read;
+    /// let mut reader = explode::ExplodeReader::new(some_file);
+    /// // once to measure...
+    /// let len = std::io::copy(&mut reader, &mut std::io::sink())?;
+    /// // ...and once to extract
+    /// reader.rewind()?;
+    /// let mut decompressed = Vec::with_capacity(len as usize);
+    /// reader.read_to_end(&mut decompressed)?;
+    /// assert_eq!(decompressed.len(), 13);
+    /// # Ok(()) }
+    /// ```
+    pub fn rewind(&mut self) -> Result<()> {
+        self.inner
+            .seek(SeekFrom::Current(-(self.state.consumed as i64)))?;
+        self.reset();
+        Ok(())
+    }
+}
+
+impl<R, C> Read for ExplodeReader<R, C>
+where
+    R: Read,
+    C: Checksum,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.read_uninit(as_uninit(buf))
+    }
+
+    #[cfg(feature = "nightly")]
+    fn read_buf(&mut self, mut cursor: BorrowedCursor<'_>) -> Result<()> {
+        // decompress straight into the cursor, without zeroing it
+        // first. only output bytes are ever written to it
+        let len = self.read_uninit(unsafe { cursor.as_mut() })?;
+        // SAFETY: read_uninit initialized the first len bytes
+        unsafe { cursor.advance(len) };
+        Ok(())
+    }
+}
+
+impl<R, C> ExplodeReader<R, C>
+where
+    R: Read,
+    C: Checksum,
+{
+    // read into buf, which need not be initialized. the first len
+    // bytes are afterwards
+    fn read_uninit(&mut self, buf: &mut [MaybeUninit<u8>]) -> Result<usize> {
+        let mut src = SyncSource(&mut self.inner);
+        let len = match poll_read(&mut self.state, &mut src, buf) {
+            Poll::Ready(result) => result?,
+            Poll::Pending => unreachable!("sync input is always ready"),
+        };
+        // SAFETY: poll_read initialized the first len bytes
+        self.checksum.update(unsafe { assume_init(&buf[..len]) });
+        Ok(len)
+    }
+}
+
+impl<R> Input<R> {
+    fn get_ref(&self) -> &R {
+        match self {
+            Input::Unbuffered { inner, .. } => inner,
+            Input::Buffered(inner) => inner.get_ref(),
+            Input::Direct { inner, .. } => inner,
+        }
+    }
+
+    fn get_mut(&mut self) -> &mut R {
+        match self {
+            Input::Unbuffered { inner, .. } => inner,
+            Input::Buffered(inner) => inner.get_mut(),
+            Input::Direct { inner, .. } => inner,
+        }
+    }
+}
+
+impl<R: std::fmt::Debug> std::fmt::Debug for Input<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Input::Unbuffered { inner, .. } => inner.fmt(f),
+            Input::Buffered(inner) => inner.fmt(f),
+            Input::Direct { inner, .. } => inner.fmt(f),
+        }
+    }
+}
+
+impl<R: Read> Read for Input<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            Input::Unbuffered {
+                byte: byte @ Some(_),
+                ..
+            } => {
+                match buf.first_mut() {
+                    Some(b) => *b = byte.take().unwrap(),
+                    None => return Ok(0),
+                }
+                Ok(1)
+            }
+            Input::Unbuffered { inner, .. } => inner.read(buf),
+            Input::Buffered(inner) => inner.read(buf),
+            Input::Direct { inner, .. } => inner.read(buf),
+        }
+    }
+}
+
+impl<R: Read + Seek> Seek for Input<R> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        match self {
+            // inner is one past a held byte, so seek from where it was
+            Input::Unbuffered { inner, byte } => {
+                let pos = match (pos, byte.take()) {
+                    (SeekFrom::Current(n), Some(_)) => {
+                        SeekFrom::Current(n - 1)
+                    }
+                    (pos, _) => pos,
+                };
+                inner.seek(pos)
+            }
+            Input::Buffered(inner) => inner.seek(pos),
+            Input::Direct { inner, .. } => inner.seek(pos),
+        }
+    }
+}
+
+impl<R: Read> BufRead for Input<R> {
+    fn fill_buf(&mut self) -> Result<&[u8]> {
+        match self {
+            Input::Unbuffered { inner, byte } => {
+                if byte.is_none() {
+                    let mut b = 0;
+                    if inner.read(std::slice::from_mut(&mut b))? > 0 {
+                        *byte = Some(b);
+                    }
+                }
+                Ok(byte.as_slice())
+            }
+            Input::Buffered(inner) => inner.fill_buf(),
+            Input::Direct { inner, fill, .. } => fill(inner),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Input::Unbuffered { byte, .. } => {
+                if amt > 0 {
+                    *byte = None;
+                }
+            }
+            Input::Buffered(inner) => inner.consume(amt),
+            Input::Direct { inner, consume, .. } => consume(inner, amt),
+        }
+    }
+}
+
+// read one byte from inner, retrying if interrupted
+fn read_byte<R: Read>(inner: &mut R, byte: &mut u8) -> Result<usize> {
+    loop {
+        match inner.read(std::slice::from_mut(byte)) {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+// fill the buffer in inner, retrying if interrupted
+fn fill_buf<R: BufRead>(inner: &mut R) -> Result<&[u8]> {
+    loop {
+        match inner.fill_buf() {
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            // work around the borrow checker, which can't tell that
+            // the loop ends here
+            Ok(_) => return inner.fill_buf(),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl ReadState {
+    pub(crate) fn new(dec: Explode) -> Self {
+        ReadState {
+            dec,
+            consumed: 0,
+            multi_member: false,
+            members: 0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.dec.reset();
+        self.consumed = 0;
+    }
+}
+
+// input for poll_read. sync input is always ready, but async input
+// may not be
+pub(crate) trait Source {
+    // get more input, which is only empty at the end of the input
+    fn poll_fill(&mut self) -> Poll<Result<&[u8]>>;
+    fn consume(&mut self, amt: usize);
+}
+
+// the input of an ExplodeReader, as a Source
+struct SyncSource<'a, R>(&'a mut Input<R>);
+
+impl<'a, R: Read> Source for SyncSource<'a, R> {
+    fn poll_fill(&mut self) -> Poll<Result<&[u8]>> {
+        Poll::Ready(self.0.fill_buf())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+// get more input from src, retrying if interrupted
+fn poll_fill<S: Source>(src: &mut S) -> Poll<Result<&[u8]>> {
+    loop {
+        match src.poll_fill() {
+            Poll::Ready(Err(e)) if e.kind() == ErrorKind::Interrupted => {
+                continue
+            }
+            // work around the borrow checker, as in fill_buf
+            Poll::Ready(Ok(_)) => return src.poll_fill(),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        }
+    }
+}
+
+// decompress from src into buf, for both ExplodeReader and
+// AsyncExplodeReader, and return the output length. buf need not be
+// initialized, but the output in it is. output is returned before
+// anything that could fail or wait, so none is lost
+pub(crate) fn poll_read<S: Source>(
+    state: &mut ReadState,
+    src: &mut S,
+    buf: &mut [MaybeUninit<u8>],
+) -> Poll<Result<usize>> {
+    let mut len = 0;
+    loop {
+        if state.dec.done() {
+            // the end of a stream, so start the next if there is one
+            if len > 0 || !state.multi_member {
+                return Poll::Ready(Ok(len));
+            }
+            match poll_fill(src)? {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready([]) => return Poll::Ready(Ok(0)),
+                Poll::Ready(_) => state.reset(),
+            }
+        }
+        if len == buf.len() {
+            return Poll::Ready(Ok(len));
+        }
+
+        let input = match poll_fill(src) {
+            Poll::Ready(Ok(input)) => input,
+            _ if len > 0 => return Poll::Ready(Ok(len)),
+            Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+            Poll::Pending => return Poll::Pending,
+        };
+        let eof = input.is_empty();
+        let (used, n) = state
+            .dec
+            .feed_slice_into_uninit(input, &mut buf[len..])
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e))?;
+        src.consume(used);
+        state.consumed += used as u64;
+        len += n;
+
+        if !state.dec.done() && eof && n == 0 {
+            // out of input
+            if len > 0 {
+                return Poll::Ready(Ok(len));
+            }
+            state
+                .dec
+                .finish()
+                .map_err(|e| IOError::new(ErrorKind::UnexpectedEof, e))?;
+        }
+        if state.dec.done() {
+            state.members += 1;
+        }
+    }
+}
+
+// decompress from inner into buf with dec, one byte at a time
+fn read_with<R: Read, S: AsRef<[u8]> + AsMut<[u8]>>(
+    dec: &mut Explode<S>,
+    inner: &mut R,
+    buf: &mut [u8],
+) -> Result<usize> {
+    if dec.done() {
+        return Ok(0);
+    }
+
+    let mut decbuf = dec.with_buffer(buf);
+    loop {
+        // after a full buffer, the last byte is still held by dec
+        let mut byte = 0;
+        if decbuf.wants_input() && read_byte(inner, &mut byte)? == 0 {
+            break;
+        }
+
+        match decbuf.feed(byte) {
+            Ok(()) => return Ok(decbuf.len()),
+            Err(Error::IncompleteInput) => continue,
+            Err(e) => return Err(IOError::new(ErrorKind::InvalidData, e)),
+        }
+    }
+
+    // out of input
+    let len = decbuf.len();
+    match dec.finish() {
+        Ok(()) => Ok(len),
+        Err(e) => Err(IOError::new(ErrorKind::UnexpectedEof, e)),
+    }
+}
+
+// a Read adapter that borrows both the decompressor and the input
+pub(crate) struct ReadAdapter<'a, R, S> {
+    pub(crate) dec: &'a mut Explode<S>,
+    pub(crate) inner: &'a mut R,
+}
+
+impl<'a, R, S> Read for ReadAdapter<'a, R, S>
+where
+    R: Read,
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        read_with(self.dec, self.inner, buf)
+    }
+}
+
+/// An iterator over the decompressed bytes of an
+/// [`ExplodeReader`](struct.ExplodeReader.html).
+///
+/// This is created by the [`IntoIterator`][IntoIterator]
+/// implementation on `ExplodeReader`. It decompresses in chunks
+/// internally, rather than one byte at a time. After an error is
+/// returned, the iterator ends.
+///
+///  [IntoIterator]: https://doc.rust-lang.org/std/iter/trait.IntoIterator.html
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// # let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// # let some_file = std::io::Cursor::new(&bytes);
+/// let reader = explode::ExplodeReader::new(some_file);
+/// for byte in reader {
+///     println!("{}", byte?);
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct ExplodeBytes<R, C = NoChecksum> {
+    reader: ExplodeReader<R, C>,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    failed: bool,
+}
+
+impl<R, C> IntoIterator for ExplodeReader<R, C>
+where
+    R: Read,
+    C: Checksum,
+{
+    type Item = Result<u8>;
+    type IntoIter = ExplodeBytes<R, C>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ExplodeBytes {
+            reader: self,
+            buf: vec![0; 4096],
+            pos: 0,
+            len: 0,
+            failed: false,
+        }
+    }
+}
+
+impl<R, C> Iterator for ExplodeBytes<R, C>
+where
+    R: Read,
+    C: Checksum,
+{
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.len {
+            if self.failed {
+                return None;
+            }
+            match self.reader.read(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(len) => {
+                    self.pos = 0;
+                    self.len = len;
+                }
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        self.pos += 1;
+        Some(Ok(self.buf[self.pos - 1]))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::ExplodeReader;
-    use crate::examples::EXAMPLES;
+    use crate::examples::{uncoded, BitWriter, EXAMPLES};
+    use crate::{Error, Explode, ExplodeConfig};
     use std::io::{Cursor, ErrorKind, Read};
 
     #[test]
@@ -99,6 +956,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reader_empty() {
+        let encoded = uncoded(b"");
+        let mut r = ExplodeReader::new(Cursor::new(&encoded));
+        let mut buf = [0; 16];
+        assert_eq!(r.read(&mut buf).unwrap(), 0);
+        assert_eq!(r.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn reader_iter() {
+        for (encoded, decoded) in EXAMPLES {
+            let r = ExplodeReader::new(Cursor::new(encoded));
+            let ours: Vec<u8> =
+                r.into_iter().collect::<Result<_, _>>().unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            // errors end the iteration
+            let r = ExplodeReader::new(Cursor::new(
+                &encoded[..encoded.len() - 1],
+            ));
+            let results: Vec<_> = r.into_iter().collect();
+            assert!(results.last().unwrap().is_err());
+            assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        }
+    }
+
     #[test]
     fn reader_small() {
         for (encoded, decoded) in EXAMPLES {
@@ -112,6 +996,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reader_skip() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut r = ExplodeReader::new(Cursor::new(encoded));
+            let half = decoded.len() / 2;
+            assert_eq!(r.skip(half as u64).unwrap(), half as u64);
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(decoded[half..], ours[..]);
+
+            let mut r = ExplodeReader::new(Cursor::new(encoded));
+            let len = decoded.len() as u64;
+            assert_eq!(r.skip(len + 100).unwrap(), len);
+            assert_eq!(r.skip(1).unwrap(), 0);
+        }
+    }
+
     #[test]
     fn reader_incomplete() {
         for (encoded, decoded) in EXAMPLES {
@@ -126,10 +1027,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reader_tolerant() {
+        use crate::examples::BitWriter;
+        use crate::ExplodeConfig;
+
+        let data = b"no end code here";
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        for b in data.iter() {
+            out.literal(*b);
+        }
+        let encoded = out.finish();
+
+        let config = ExplodeConfig::new().tolerant(true);
+        let dec = Explode::new_with(config);
+        let mut r = ExplodeReader::with_explode(Cursor::new(&encoded), dec);
+        let mut ours = vec![];
+        r.read_to_end(&mut ours).unwrap();
+        assert_eq!(&data[..], &ours[..]);
+
+        let mut r = ExplodeReader::new(Cursor::new(&encoded));
+        match r.read_to_end(&mut ours) {
+            Err(e) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+            _ => panic!("incorrectly parsed stream without end code"),
+        }
+    }
+
     #[test]
     fn reader_extra() {
         for (encoded, decoded) in EXAMPLES {
-            let mut encodedplus: Vec<u8> = encoded.iter().cloned().collect();
+            let mut encodedplus: Vec<u8> = encoded.to_vec();
             encodedplus.push(42);
             let mut inner = Cursor::new(&encodedplus);
             let mut r = ExplodeReader::new(&mut inner);
@@ -140,6 +1068,517 @@ mod tests {
             ours.clear();
             inner.read_to_end(&mut ours).unwrap();
             assert_eq!(vec![42], ours);
+
+            // buffered readers read ahead
+            let mut r = ExplodeReader::buffered(Cursor::new(&encodedplus));
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            assert_eq!(r.buffer(), &[42]);
+        }
+    }
+
+    #[test]
+    fn reader_into_inner() {
+        use std::io::BufReader;
+
+        for (encoded, decoded) in EXAMPLES {
+            let mut encodedplus: Vec<u8> = encoded.to_vec();
+            encodedplus.push(42);
+
+            // read ahead to the end
+            let mut r = ExplodeReader::buffered(Cursor::new(&encodedplus));
+            assert_eq!(r.get_ref().position(), 0);
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            r.get_mut().set_position(0);
+            assert_eq!(r.into_inner().position(), 0);
+
+            // or right after the stream
+            let mut r = ExplodeReader::new(Cursor::new(&encodedplus));
+            r.read_to_end(&mut vec![]).unwrap();
+            let mut rest = vec![];
+            r.into_inner().read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, [42]);
+            let inner =
+                BufReader::with_capacity(7, Cursor::new(&encodedplus));
+            let mut r = ExplodeReader::from_bufread(inner);
+            r.read_to_end(&mut vec![]).unwrap();
+            let mut rest = vec![];
+            r.into_inner().read_to_end(&mut rest).unwrap();
+            assert_eq!(rest, [42]);
+        }
+    }
+
+    #[test]
+    fn reader_into_parts() {
+        for (encoded, decoded) in EXAMPLES {
+            // more trailing data than fits in the input buffer
+            let trailer: Vec<u8> = (0..20000).map(|i| i as u8).collect();
+            let mut data = encoded.to_vec();
+            data.extend_from_slice(&trailer);
+
+            for size in [1, 7, 4096].iter() {
+                let mut r = ExplodeReader::buffered(Cursor::new(&data));
+                let mut buf = vec![0; *size];
+                let mut ours = Vec::with_capacity(decoded.len());
+                loop {
+                    match r.read(&mut buf).unwrap() {
+                        0 => break,
+                        n => ours.extend_from_slice(&buf[..n]),
+                    }
+                }
+                assert_eq!(*decoded, &ours[..]);
+
+                let (inner, unused) = r.into_parts();
+                let mut rest = vec![];
+                (&unused[..]).chain(inner).read_to_end(&mut rest).unwrap();
+                assert_eq!(rest, trailer);
+            }
+        }
+    }
+
+    #[test]
+    fn reader_buffered() {
+        // counts calls to read
+        struct Counting<R>(R, usize);
+        impl<R: Read> Read for Counting<R> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.1 += 1;
+                self.0.read(buf)
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let inner = Counting(Cursor::new(encoded), 0);
+            let mut r = ExplodeReader::buffered(inner);
+            let mut ours = Vec::with_capacity(decoded.len());
+            let mut buf = [0; 7];
+            loop {
+                let len = r.read(&mut buf).unwrap();
+                if len == 0 {
+                    break;
+                }
+                ours.extend_from_slice(&buf[..len]);
+            }
+            assert_eq!(*decoded, &ours[..]);
+            let reads = encoded.len() / super::INPUT_SIZE + 2;
+            assert!(r.get_ref().1 <= reads);
+        }
+    }
+
+    #[test]
+    fn reader_from_bufread() {
+        use std::io::BufReader;
+
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 7, 4096].iter() {
+                let inner = BufReader::with_capacity(*size, &encoded[..]);
+                let mut r = ExplodeReader::from_bufread(inner);
+                let mut ours = Vec::with_capacity(decoded.len());
+                r.read_to_end(&mut ours).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+            }
+
+            // exactly the stream is consumed
+            let mut encodedplus: Vec<u8> = encoded.to_vec();
+            encodedplus.push(42);
+            let mut inner = Cursor::new(&encodedplus);
+            let mut r = ExplodeReader::from_bufread(&mut inner);
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            assert!(r.buffer().is_empty());
+
+            ours.clear();
+            inner.read_to_end(&mut ours).unwrap();
+            assert_eq!(vec![42], ours);
+
+            // even after only reading the header
+            let mut inner = Cursor::new(encoded);
+            ExplodeReader::from_bufread(&mut inner)
+                .read_header()
+                .unwrap();
+            assert_eq!(inner.position(), 2);
+        }
+    }
+
+    #[test]
+    fn reader_lines_lossy() {
+        for (encoded, decoded) in EXAMPLES {
+            let r = ExplodeReader::new(Cursor::new(encoded));
+            let ours: Vec<String> =
+                r.lines_lossy().collect::<Result<_, _>>().unwrap();
+            let text = String::from_utf8_lossy(decoded);
+            let theirs: Vec<&str> = text.lines().collect();
+            assert_eq!(theirs, ours);
+        }
+
+        let encoded = uncoded(b"one\r\ntw\xffo\n\nthree");
+        let r = ExplodeReader::new(Cursor::new(&encoded));
+        let ours: Vec<String> =
+            r.lines_lossy().collect::<Result<_, _>>().unwrap();
+        assert_eq!(ours, vec!["one", "tw\u{fffd}o", "", "three"]);
+    }
+
+    #[test]
+    fn reader_with_explode() {
+        for (encoded, decoded) in EXAMPLES {
+            // feed in the header by hand
+            let mut dec = Explode::new();
+            let mut buf = [0; 1];
+            let mut decbuf = dec.with_buffer(&mut buf);
+            for b in encoded[..2].iter() {
+                match decbuf.feed(*b) {
+                    Err(Error::IncompleteInput) => (),
+                    _ => panic!("header did not request more input"),
+                }
+            }
+
+            let mut r = dec.into_reader(Cursor::new(&encoded[2..]));
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    fn reader_header() {
+        for (encoded, decoded) in EXAMPLES {
+            let mut inner = Cursor::new(&encoded[..]);
+            let mut r = ExplodeReader::new(&mut inner);
+            let header = r.read_header().unwrap();
+            assert_eq!(header.coded_literals, encoded[0] == 1);
+            assert_eq!(header.dict_size, encoded[1]);
+            // asking again is fine
+            assert_eq!(r.read_header().unwrap(), header);
+
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            // and also after the end
+            assert_eq!(r.read_header().unwrap(), header);
+        }
+
+        // only the header is read
+        let (encoded, _) = EXAMPLES[0];
+        let mut inner = Cursor::new(encoded);
+        ExplodeReader::new(&mut inner).read_header().unwrap();
+        assert_eq!(inner.position(), 2);
+
+        // or used, when reading ahead
+        let mut r = ExplodeReader::buffered(Cursor::new(encoded));
+        r.read_header().unwrap();
+        assert_eq!(r.buffer(), &encoded[2..]);
+
+        // truncated and bad headers
+        let mut r = ExplodeReader::new(Cursor::new(&[0x00]));
+        let err = r.read_header().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        let mut r = ExplodeReader::new(Cursor::new(&[0x00, 0x07, 0x00]));
+        let err = r.read_header().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reader_reset() {
+        for (encoded, decoded) in EXAMPLES {
+            // two streams back to back
+            let mut data = encoded.to_vec();
+            data.extend_from_slice(encoded);
+            let mut r = ExplodeReader::new(Cursor::new(&data));
+            for _ in 0..2 {
+                let mut ours = Vec::with_capacity(decoded.len());
+                r.read_to_end(&mut ours).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+                r.reset();
+            }
+            let mut ours = vec![];
+            let err = r.read_to_end(&mut ours).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        }
+    }
+
+    #[test]
+    fn reader_finished_cleanly() {
+        let tolerant =
+            || Explode::new_with(ExplodeConfig::new().tolerant(true));
+        for (encoded, decoded) in EXAMPLES {
+            let mut r = ExplodeReader::with_explode(&encoded[..], tolerant());
+            assert!(!r.finished_cleanly());
+            let mut ours = vec![];
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+            assert!(r.finished_cleanly());
+
+            // truncated is an error, and never clean
+            let cut = &encoded[..encoded.len() - 1];
+            let mut r = ExplodeReader::new(cut);
+            let err = r.read_to_end(&mut vec![]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+            assert!(!r.finished_cleanly());
+        }
+
+        // stopping between tokens is accepted, but not clean
+        let mut out = BitWriter::new();
+        out.header(0, 4);
+        out.literal(b'x');
+        let encoded = out.finish();
+        let mut r = ExplodeReader::with_explode(&encoded[..], tolerant());
+        let mut ours = vec![];
+        r.read_to_end(&mut ours).unwrap();
+        assert_eq!(ours, b"x");
+        assert!(!r.finished_cleanly());
+    }
+
+    #[test]
+    fn reader_multi_member() {
+        use std::io::BufReader;
+
+        for (i, (first, first_decoded)) in EXAMPLES.iter().enumerate() {
+            let (second, second_decoded) = EXAMPLES[(i + 1) % EXAMPLES.len()];
+            let data = [*first, second].concat();
+            let decoded = [*first_decoded, second_decoded].concat();
+
+            let mut r =
+                ExplodeReader::new(Cursor::new(&data)).multi_member(true);
+            let mut ours = vec![];
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(decoded, ours);
+            assert_eq!(r.member_count(), 2);
+
+            // small reads, straight from a buffer
+            let buffered = BufReader::with_capacity(5, Cursor::new(&data));
+            let mut r =
+                ExplodeReader::from_bufread(buffered).multi_member(true);
+            let mut ours = vec![];
+            let mut buf = [0; 7];
+            loop {
+                match r.read(&mut buf).unwrap() {
+                    0 => break,
+                    n => ours.extend_from_slice(&buf[..n]),
+                }
+            }
+            assert_eq!(decoded, ours);
+            assert_eq!(r.member_count(), 2);
+
+            // without it, only the first
+            let mut r = ExplodeReader::new(Cursor::new(&data));
+            let mut ours = vec![];
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*first_decoded, &ours[..]);
+            assert_eq!(r.member_count(), 1);
+            assert_eq!(r.read(&mut buf).unwrap(), 0);
+            assert_eq!(r.member_count(), 1);
+
+            // a partial member after the last is an error
+            let mut data = data.clone();
+            data.extend_from_slice(&first[..first.len() / 2]);
+            let mut r =
+                ExplodeReader::new(Cursor::new(&data)).multi_member(true);
+            let err = r.read_to_end(&mut vec![]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+            assert_eq!(r.member_count(), 2);
+        }
+
+        // an error after a stream comes after its output
+        let (first, first_decoded) = EXAMPLES[0];
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("oops"))
+            }
+        }
+        let inner = Cursor::new(first).chain(Failing);
+        let mut r = ExplodeReader::new(inner).multi_member(true);
+        let mut ours = vec![0; first_decoded.len() + 1];
+        let len = r.read(&mut ours).unwrap();
+        assert_eq!(first_decoded, &ours[..len]);
+        assert_eq!(r.member_count(), 1);
+        let err = r.read(&mut ours).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Other);
+    }
+
+    #[test]
+    fn reader_rewind() {
+        use std::io::{BufReader, Seek};
+
+        fn check<R: Read + Seek>(r: &mut ExplodeReader<R>, decoded: &[u8]) {
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(decoded, &ours[..]);
+
+            // from the end, and from partway through
+            r.rewind().unwrap();
+            let mut ours = vec![0; decoded.len() / 2];
+            r.read_exact(&mut ours).unwrap();
+            r.rewind().unwrap();
+            let mut ours = Vec::with_capacity(decoded.len());
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(decoded, &ours[..]);
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            // not at the start of inner, with data after
+            let mut data = vec![42; 3];
+            data.extend_from_slice(encoded);
+            data.extend_from_slice(&[42; 3]);
+            let mut inner = Cursor::new(&data);
+            inner.set_position(3);
+
+            check(&mut ExplodeReader::new(inner.clone()), decoded);
+            check(&mut ExplodeReader::buffered(inner.clone()), decoded);
+            let buffered = BufReader::with_capacity(7, inner);
+            check(&mut ExplodeReader::from_bufread(buffered), decoded);
+        }
+    }
+
+    #[cfg(feature = "nightly")]
+    #[test]
+    fn reader_read_buf() {
+        use std::io::BorrowedBuf;
+        use std::mem::MaybeUninit;
+
+        for (encoded, decoded) in EXAMPLES {
+            let mut r = ExplodeReader::new(Cursor::new(encoded));
+            let mut storage = [MaybeUninit::uninit(); 100];
+            let mut ours = Vec::with_capacity(decoded.len());
+            loop {
+                let mut buf: BorrowedBuf = (&mut storage[..]).into();
+                r.read_buf(buf.unfilled()).unwrap();
+                if buf.len() == 0 {
+                    break;
+                }
+                ours.extend_from_slice(buf.filled());
+            }
+            assert_eq!(*decoded, &ours[..]);
+
+            // one read can fill a large buffer
+            let mut r = ExplodeReader::new(Cursor::new(encoded));
+            let mut storage = vec![MaybeUninit::uninit(); decoded.len()];
+            let mut buf: BorrowedBuf = (&mut storage[..]).into();
+            r.read_buf(buf.unfilled()).unwrap();
+            assert_eq!(*decoded, buf.filled());
+
+            // nothing past the output is written, where the default
+            // read_buf would zero the whole buffer first
+            let mut r = ExplodeReader::buffered(Cursor::new(encoded));
+            let mut storage =
+                vec![MaybeUninit::new(0xaa); decoded.len() + 10];
+            let mut buf: BorrowedBuf = (&mut storage[..]).into();
+            r.read_buf(buf.unfilled()).unwrap();
+            assert_eq!(*decoded, buf.filled());
+            let rest = &storage[decoded.len()..];
+            // SAFETY: storage was initialized, and only output bytes
+            // were written to it
+            assert!(rest.iter().all(|b| unsafe { b.assume_init() } == 0xaa));
+
+            // read_to_end uses read_buf
+            let mut r = ExplodeReader::new(Cursor::new(encoded));
+            let mut ours = Vec::new();
+            r.read_to_end(&mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+        }
+    }
+
+    #[test]
+    fn reader_checksum() {
+        #[derive(Debug, Default)]
+        struct Sum(u64, usize);
+        impl crate::Checksum for Sum {
+            fn update(&mut self, data: &[u8]) {
+                self.0 += data.iter().map(|b| *b as u64).sum::<u64>();
+                self.1 += data.len();
+            }
+            fn finalize(self) -> u64 {
+                self.0
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let expected: u64 = decoded.iter().map(|b| *b as u64).sum();
+            let r = ExplodeReader::new(Cursor::new(encoded));
+            let mut r = r.with_checksum(Sum::default());
+            let mut buf = [0; 7];
+            while r.read(&mut buf).unwrap() > 0 {}
+            assert_eq!(r.checksum().1, decoded.len());
+            assert_eq!(
+                crate::Checksum::finalize(r.into_checksum()),
+                expected
+            );
+
+            // also through the byte iterator
+            let r = ExplodeReader::new(Cursor::new(encoded))
+                .with_checksum(Sum::default());
+            let mut iter = r.into_iter();
+            for b in iter.by_ref() {
+                b.unwrap();
+            }
+            assert_eq!(iter.reader.checksum().0, expected);
+        }
+    }
+
+    #[cfg(feature = "crc")]
+    #[test]
+    fn reader_crc32() {
+        // bit at a time, for reference
+        fn crc32(data: &[u8]) -> u32 {
+            let mut crc = !0u32;
+            for b in data.iter() {
+                crc ^= *b as u32;
+                for _ in 0..8 {
+                    crc =
+                        (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+                }
+            }
+            !crc
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let mut r = ExplodeReader::new(Cursor::new(encoded)).with_crc32();
+            assert_eq!(r.crc32(), 0);
+            let mut buf = [0; 7];
+            while r.read(&mut buf).unwrap() > 0 {}
+            assert_eq!(r.crc32(), crc32(decoded));
+            let value = crate::Checksum::finalize(r.into_checksum());
+            assert_eq!(value, crc32(decoded) as u64);
+        }
+    }
+
+    #[test]
+    fn reader_interrupted() {
+        // interrupts before every byte
+        struct Interrupting<R>(R, bool);
+        impl<R: Read> Read for Interrupting<R> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.1 = !self.1;
+                if self.1 {
+                    return Err(ErrorKind::Interrupted.into());
+                }
+                self.0.read(buf)
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let inner = Interrupting(Cursor::new(encoded), false);
+            let mut r = ExplodeReader::new(inner);
+            assert_eq!(r.read_header().unwrap().dict_size, encoded[1]);
+            let mut ours = Vec::with_capacity(decoded.len());
+            let mut buf = [0; 7];
+            loop {
+                let len = r.read(&mut buf).unwrap();
+                if len == 0 {
+                    break;
+                }
+                ours.extend_from_slice(&buf[..len]);
+            }
+            assert_eq!(*decoded, &ours[..]);
+
+            let mut inner = Interrupting(Cursor::new(encoded), false);
+            let mut ours = vec![];
+            Explode::new().copy_to(&mut inner, &mut ours).unwrap();
+            assert_eq!(*decoded, &ours[..]);
         }
     }
 }
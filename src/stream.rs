@@ -0,0 +1,229 @@
+use crate::Explode;
+
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+use std::io::{Error as IOError, ErrorKind, Result};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// the most output in one decompressed chunk
+const CHUNK_SIZE: usize = 4096;
+// how much room for chunks to allocate at once
+const OUT_SIZE: usize = 4 * CHUNK_SIZE;
+
+/// A [`Stream`][Stream] of decompressed chunks.
+///
+///  [Stream]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
+///
+/// This is created by [`explode_stream`](fn.explode_stream.html).
+/// After an error is returned, the stream ends.
+#[derive(Debug)]
+pub struct ExplodeStream<S> {
+    inner: Pin<Box<S>>,
+    dec: Explode,
+    chunk: Bytes,
+    // decompressed chunks are split off the front of this
+    out: BytesMut,
+    eof: bool,
+    failed: bool,
+}
+
+/// Decompress a [`Stream`][Stream] of compressed chunks into a
+/// stream of decompressed chunks.
+///
+///  [Stream]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
+///
+/// Compressed chunks can be split anywhere, even in the middle of a
+/// token. Decompressed chunks are at most 4096 bytes long, and are
+/// only produced as the result is polled, so a slow consumer will
+/// also slow down reads from `input`.
+///
+/// This is only available with the `futures` feature.
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use futures::{executor, stream, StreamExt};
+///
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let chunks = bytes.chunks(3).map(|c| Ok(c.to_vec().into()));
+/// let mut result = explode::explode_stream(stream::iter(chunks));
+/// let mut decompressed = vec![];
+/// executor::block_on(async {
+///     while let Some(chunk) = result.next().await {
+///         decompressed.extend_from_slice(&chunk?);
+///     }
+///     Ok::<_, std::io::Error>(())
+/// })?;
+/// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+pub fn explode_stream<S>(input: S) -> ExplodeStream<S>
+where
+    S: Stream<Item = Result<Bytes>>,
+{
+    ExplodeStream {
+        inner: Box::pin(input),
+        dec: Explode::new(),
+        chunk: Bytes::new(),
+        out: BytesMut::new(),
+        eof: false,
+        failed: false,
+    }
+}
+
+impl<S> ExplodeStream<S> {
+    // run the decompressor over the current chunk
+    fn step(&mut self) -> Result<Bytes> {
+        // chunks are split from out, and share its memory until it
+        // runs low. it is reused in place if they were all dropped
+        if self.out.capacity() < CHUNK_SIZE {
+            self.out.reserve(OUT_SIZE);
+        }
+        self.out.resize(CHUNK_SIZE, 0);
+        let (used, len) = self
+            .dec
+            .feed_slice_into(&self.chunk, &mut self.out)
+            .map_err(|e| IOError::new(ErrorKind::InvalidData, e))?;
+        self.chunk = self.chunk.slice(used..);
+        self.out.truncate(len);
+
+        if self.eof && len < CHUNK_SIZE {
+            // out of input
+            self.dec
+                .finish()
+                .map_err(|e| IOError::new(ErrorKind::UnexpectedEof, e))?;
+        }
+
+        Ok(self.out.split().freeze())
+    }
+}
+
+impl<S> Stream for ExplodeStream<S>
+where
+    S: Stream<Item = Result<Bytes>>,
+{
+    type Item = Result<Bytes>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.failed || this.dec.done() {
+                return Poll::Ready(None);
+            }
+
+            if this.chunk.is_empty() && !this.eof {
+                match this.inner.as_mut().poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Some(Ok(chunk))) => this.chunk = chunk,
+                    Poll::Ready(Some(Err(e))) => {
+                        this.failed = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                    Poll::Ready(None) => this.eof = true,
+                }
+            }
+
+            match this.step() {
+                Ok(out) if out.is_empty() => continue,
+                Ok(out) => return Poll::Ready(Some(Ok(out))),
+                Err(e) => {
+                    this.failed = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explode_stream;
+    use crate::examples::EXAMPLES;
+    use futures::{executor, stream, StreamExt};
+    use std::io::{Error, ErrorKind, Result};
+
+    fn collect<I>(chunks: I) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = Result<bytes::Bytes>>,
+    {
+        let mut result = explode_stream(stream::iter(chunks));
+        executor::block_on(async {
+            let mut ours = vec![];
+            while let Some(chunk) = result.next().await {
+                let chunk = chunk?;
+                assert!(!chunk.is_empty() && chunk.len() <= 4096);
+                ours.extend_from_slice(&chunk);
+            }
+            Ok(ours)
+        })
+    }
+
+    #[test]
+    fn stream() {
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 7, 4096].iter() {
+                let chunks = encoded
+                    .chunks(*size)
+                    .map(|c| Ok(bytes::Bytes::copy_from_slice(c)));
+                let ours = collect(chunks).unwrap();
+                assert_eq!(*decoded, &ours[..]);
+            }
+
+            // empty chunks are skipped
+            let (a, b) = encoded.split_at(encoded.len() / 2);
+            let chunks = vec![
+                Ok(bytes::Bytes::new()),
+                Ok(bytes::Bytes::copy_from_slice(a)),
+                Ok(bytes::Bytes::new()),
+                Ok(bytes::Bytes::copy_from_slice(b)),
+            ];
+            let ours = collect(chunks).unwrap();
+            assert_eq!(*decoded, &ours[..]);
+
+            // small chunks share memory
+            let chunks = encoded
+                .chunks(1)
+                .map(|c| Ok(bytes::Bytes::copy_from_slice(c)));
+            let result = explode_stream(stream::iter(chunks));
+            let ours: Vec<Result<bytes::Bytes>> =
+                executor::block_on(result.collect());
+            let ours: Vec<_> = ours.into_iter().map(Result::unwrap).collect();
+            let shared = ours
+                .windows(2)
+                .filter(|w| w[0].as_ptr_range().end == w[1].as_ptr())
+                .count();
+            assert!(shared * 2 >= ours.len().saturating_sub(1));
+        }
+    }
+
+    #[test]
+    fn stream_errors() {
+        let (encoded, _) = EXAMPLES[2];
+
+        // truncated
+        let chunks = encoded[..100]
+            .chunks(7)
+            .map(|c| Ok(bytes::Bytes::copy_from_slice(c)));
+        let err = collect(chunks).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        // bad data
+        let chunks = vec![Ok(bytes::Bytes::from_static(&[0x00, 0x07]))];
+        let err = collect(chunks).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // errors from the inner stream are passed on, then it ends
+        let chunks = vec![
+            Ok(bytes::Bytes::copy_from_slice(&encoded[..10])),
+            Err(Error::other("oops")),
+            Ok(bytes::Bytes::copy_from_slice(&encoded[10..])),
+        ];
+        let mut result = explode_stream(stream::iter(chunks));
+        let items: Vec<_> = executor::block_on(result.by_ref().collect());
+        let last = items.last().unwrap().as_ref().unwrap_err();
+        assert_eq!(last.kind(), ErrorKind::Other);
+    }
+}
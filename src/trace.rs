@@ -0,0 +1,27 @@
+// tracing helpers, which compile to nothing without the tracing feature
+
+// emit an event at the given level, like tracing::event!
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($level:ident, $($args:tt)*) => {
+        tracing::event!(tracing::Level::$level, $($args)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+    ($level:ident, $($args:tt)*) => {};
+}
+
+// enter a debug span for the rest of the enclosing block
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($args:tt)*) => {
+        let _span = tracing::debug_span!($($args)*).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($args:tt)*) => {};
+}
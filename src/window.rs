@@ -0,0 +1,168 @@
+// the window of recent output, as a ring buffer over any storage
+
+use core::ops::Index;
+
+// copies can never reach further back than this
+pub(crate) const WINDOW_SIZE: usize = 4096;
+const MASK: usize = WINDOW_SIZE - 1;
+
+// the last WINDOW_SIZE bytes pushed, oldest first. storage past
+// WINDOW_SIZE is not used
+#[derive(Clone, Debug)]
+pub(crate) struct Window<S> {
+    buf: S,
+    // index of the oldest byte in buf, and how many bytes there are
+    start: usize,
+    len: usize,
+}
+
+impl<S> Window<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    pub(crate) fn new(buf: S) -> Self {
+        assert!(
+            buf.as_ref().len() >= WINDOW_SIZE,
+            "window storage must be at least {} bytes",
+            WINDOW_SIZE
+        );
+        Window {
+            buf,
+            start: 0,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn into_inner(self) -> S {
+        self.buf
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        WINDOW_SIZE
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.len == WINDOW_SIZE
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.start = 0;
+        self.len = 0;
+    }
+
+    // add a byte, dropping the oldest if full
+    #[inline]
+    pub(crate) fn push_back(&mut self, value: u8) {
+        let buf = &mut self.buf.as_mut()[..WINDOW_SIZE];
+        buf[(self.start + self.len) & MASK] = value;
+        if self.len == WINDOW_SIZE {
+            self.start = (self.start + 1) & MASK;
+        } else {
+            self.len += 1;
+        }
+    }
+
+    // add bytes, dropping as many of the oldest as needed
+    pub(crate) fn extend_from_slice(&mut self, data: &[u8]) {
+        let data = &data[data.len().saturating_sub(WINDOW_SIZE)..];
+        let buf = &mut self.buf.as_mut()[..WINDOW_SIZE];
+        let end = (self.start + self.len) & MASK;
+        let first = data.len().min(WINDOW_SIZE - end);
+        buf[end..end + first].copy_from_slice(&data[..first]);
+        buf[..data.len() - first].copy_from_slice(&data[first..]);
+
+        let total = self.len + data.len();
+        if total > WINDOW_SIZE {
+            self.start = (self.start + total - WINDOW_SIZE) & MASK;
+            self.len = WINDOW_SIZE;
+        } else {
+            self.len = total;
+        }
+    }
+
+    // the contents, oldest first, in two parts
+    pub(crate) fn as_slices(&self) -> (&[u8], &[u8]) {
+        let buf = &self.buf.as_ref()[..WINDOW_SIZE];
+        if self.start + self.len <= WINDOW_SIZE {
+            (&buf[self.start..self.start + self.len], &[])
+        } else {
+            let wrapped = self.start + self.len - WINDOW_SIZE;
+            (&buf[self.start..], &buf[..wrapped])
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &u8> + '_ {
+        let (a, b) = self.as_slices();
+        a.iter().chain(b.iter())
+    }
+}
+
+impl<S> Index<usize> for Window<S>
+where
+    S: AsRef<[u8]> + AsMut<[u8]>,
+{
+    type Output = u8;
+
+    #[inline]
+    fn index(&self, idx: usize) -> &u8 {
+        debug_assert!(idx < self.len);
+        &self.buf.as_ref()[..WINDOW_SIZE][(self.start + idx) & MASK]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Window, WINDOW_SIZE};
+    use alloc::collections::VecDeque;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn window_ring() {
+        let mut storage = [0; WINDOW_SIZE + 10];
+        let mut window = Window::new(&mut storage[..]);
+        let mut expected = VecDeque::new();
+
+        // sizes that wrap around at different places
+        let mut next = 0u8;
+        for n in [1, 100, 4000, 0, 5000, 4096, 3, 4095].iter() {
+            let data: Vec<u8> = (0..*n)
+                .map(|_| {
+                    next = next.wrapping_add(7);
+                    next
+                })
+                .collect();
+            if n % 2 == 0 {
+                window.extend_from_slice(&data);
+            } else {
+                for b in data.iter() {
+                    window.push_back(*b);
+                }
+            }
+            expected.extend(data);
+            while expected.len() > WINDOW_SIZE {
+                expected.pop_front();
+            }
+
+            assert_eq!(window.len(), expected.len());
+            assert!(window.iter().eq(expected.iter()));
+            for (i, b) in expected.iter().enumerate() {
+                assert_eq!(window[i], *b);
+            }
+        }
+        assert!(window.is_full());
+
+        window.clear();
+        assert_eq!(window.len(), 0);
+        assert_eq!(window.as_slices(), (&[][..], &[][..]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn window_too_small() {
+        Window::new([0; WINDOW_SIZE - 1]);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::Explode;
+
+use std::io::{Error as IOError, ErrorKind, Result, Write};
+
+// how much output to decompress before writing it to inner
+const OUTPUT_SIZE: usize = 8192;
+
+/// A [`Write`][Write] wrapper that decompresses.
+///
+///  [Write]: https://doc.rust-lang.org/std/io/trait.Write.html
+///
+/// Compressed data written here is decompressed, and the output is
+/// written to the inner writer as it becomes ready. This is the
+/// counterpart to [`ExplodeReader`](struct.ExplodeReader.html), for
+/// when the source of the data pushes it rather than being read
+/// from. When done, call [`finish`](#method.finish) to check that the
+/// whole stream was written.
+///
+/// Once the end of the compressed stream is reached, nothing more is
+/// accepted, and `write` returns 0. Anything written after the end is
+/// left to the caller.
+///
+/// This is only available with the `std` feature.
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use explode::ExplodeWriter;
+/// use std::io::Write;
+///
+/// let bytes = vec![0x00, 0x04, 0x82, 0x24, 0x25, 0x8f, 0x80, 0x7f];
+/// let mut w = ExplodeWriter::new(vec![]);
+/// w.write_all(&bytes)?;
+/// let decompressed = w.finish()?;
+/// assert_eq!(decompressed, "AIAIAIAIAIAIA".as_bytes());
+/// # Ok(()) }
+/// ```
+#[derive(Debug)]
+pub struct ExplodeWriter<W> {
+    inner: W,
+    dec: Explode,
+    buf: Box<[u8]>,
+    // output in buf that is not yet written to inner
+    pos: usize,
+    end: usize,
+}
+
+impl<W> ExplodeWriter<W>
+where
+    W: Write,
+{
+    /// Create a new decompression wrapper around `inner`.
+    pub fn new(inner: W) -> Self {
+        Self::with_explode(inner, Explode::new())
+    }
+
+    /// Create a new decompression wrapper around `inner`, continuing
+    /// from an existing decompression state `dec`.
+    ///
+    /// As with
+    /// [`ExplodeReader::with_explode`](struct.ExplodeReader.html#method.with_explode),
+    /// `dec` must be ready for the next byte written here.
+    pub fn with_explode(inner: W, dec: Explode) -> Self {
+        ExplodeWriter {
+            inner,
+            dec,
+            buf: vec![0; OUTPUT_SIZE].into_boxed_slice(),
+            pos: 0,
+            end: 0,
+        }
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying writer.
+    ///
+    /// Writing to it directly will mix with the decompressed output.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns true if the end of the compressed stream was written.
+    pub fn done(&self) -> bool {
+        self.dec.done()
+    }
+
+    /// Check that the whole compressed stream was written, and return
+    /// the inner writer.
+    ///
+    /// If the stream is not finished, this returns an error of kind
+    /// [`UnexpectedEof`][eof], unless the decompressor was configured
+    /// with
+    /// [`ExplodeConfig::tolerant`](struct.ExplodeConfig.html#method.tolerant)
+    /// and the stream stopped between tokens.
+    ///
+    ///  [eof]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.UnexpectedEof
+    pub fn finish(mut self) -> Result<W> {
+        self.dump()?;
+        if !self.dec.done() {
+            self.dec
+                .finish()
+                .map_err(|e| IOError::new(ErrorKind::UnexpectedEof, e))?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    // write out any output left in buf. on error, whatever was not
+    // written is kept for next time
+    fn dump(&mut self) -> Result<()> {
+        while self.pos < self.end {
+            match self.inner.write(&self.buf[self.pos..self.end]) {
+                Ok(0) => {
+                    return Err(IOError::new(
+                        ErrorKind::WriteZero,
+                        "failed to write decompressed data",
+                    ))
+                }
+                Ok(n) => self.pos += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W> Write for ExplodeWriter<W>
+where
+    W: Write,
+{
+    /// Decompress `buf`, and write the output to the inner writer.
+    ///
+    /// Output left over from an earlier call is written first, and an
+    /// error there is returned before any of `buf` is taken. Once some
+    /// of `buf` is taken, an error writing its output only leaves that
+    /// output for the next call, which will return the error if it
+    /// happens again.
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.dump()?;
+        let mut used = 0;
+        while !self.dec.done() {
+            let (n, len) = self
+                .dec
+                .feed_slice_into(&buf[used..], &mut self.buf)
+                .map_err(|e| IOError::new(ErrorKind::InvalidData, e))?;
+            used += n;
+            self.pos = 0;
+            self.end = len;
+            if let Err(e) = self.dump() {
+                if used == 0 {
+                    return Err(e);
+                }
+                break;
+            }
+            if len < self.buf.len() {
+                // out of input
+                break;
+            }
+        }
+        Ok(used)
+    }
+
+    /// Write out any output left over from an earlier error, and
+    /// flush the inner writer.
+    fn flush(&mut self) -> Result<()> {
+        self.dump()?;
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExplodeWriter;
+    use crate::examples::EXAMPLES;
+    use std::io::{ErrorKind, Write};
+
+    #[test]
+    fn writer() {
+        for (encoded, decoded) in EXAMPLES {
+            for size in [1, 2, 7, 100, 10000].iter() {
+                let mut w = ExplodeWriter::new(vec![]);
+                for chunk in encoded.chunks(*size) {
+                    w.write_all(chunk).unwrap();
+                }
+                assert!(w.done());
+                assert_eq!(*decoded, &w.finish().unwrap()[..]);
+            }
+        }
+    }
+
+    #[test]
+    fn writer_errors() {
+        let (encoded, decoded) = EXAMPLES[2];
+
+        // truncated
+        let mut w = ExplodeWriter::new(vec![]);
+        w.write_all(&encoded[..100]).unwrap();
+        assert!(!w.done());
+        let err = w.finish().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        // bad data
+        let mut w = ExplodeWriter::new(vec![]);
+        let err = w.write(&[0x00, 0x07]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        // nothing is taken after the end
+        let mut data = encoded.to_vec();
+        data.extend_from_slice(b"extra");
+        let mut w = ExplodeWriter::new(vec![]);
+        let mut used = 0;
+        while used < data.len() {
+            match w.write(&data[used..]).unwrap() {
+                0 => break,
+                n => used += n,
+            }
+        }
+        assert_eq!(used, encoded.len());
+        let err = w.write_all(b"more").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WriteZero);
+        assert_eq!(decoded, &w.finish().unwrap()[..]);
+    }
+
+    #[test]
+    fn writer_inner_errors() {
+        use std::io::Error;
+
+        // a sink that takes a few bytes at a time, and while flaky,
+        // fails every other call
+        struct Flaky {
+            out: Vec<u8>,
+            failed: bool,
+            flaky: bool,
+        }
+        impl Write for Flaky {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.failed = self.flaky && !self.failed;
+                if self.failed {
+                    return Err(Error::other("oops"));
+                }
+                let n = buf.len().min(3000);
+                self.out.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        for (encoded, decoded) in EXAMPLES {
+            let flaky = Flaky {
+                out: vec![],
+                failed: false,
+                flaky: true,
+            };
+            let mut w = ExplodeWriter::new(flaky);
+            for mut chunk in encoded.chunks(1000) {
+                // an error never takes any input, and taken input is
+                // never lost
+                while !chunk.is_empty() {
+                    match w.write(chunk) {
+                        Ok(n) => chunk = &chunk[n..],
+                        Err(e) => assert_eq!(e.kind(), ErrorKind::Other),
+                    }
+                }
+            }
+            while w.flush().is_err() {}
+            w.inner.flaky = false;
+            assert_eq!(*decoded, &w.finish().unwrap().out[..]);
+        }
+    }
+}
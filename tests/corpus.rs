@@ -0,0 +1,56 @@
+// decode every input in tests/corpus, and compare against the expected
+// output or error next to it. see tests/corpus/README.md
+
+use std::ffi::OsStr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+fn inputs() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension() == Some(OsStr::new("z")))
+        .collect();
+    paths.sort();
+    paths
+}
+
+// the expected result, as output bytes or an error message
+fn expected(path: &Path) -> Result<Vec<u8>, String> {
+    if let Ok(out) = std::fs::read(path.with_extension("out")) {
+        return Ok(out);
+    }
+    match std::fs::read_to_string(path.with_extension("err")) {
+        Ok(err) => Err(err.trim_end().to_owned()),
+        Err(_) => panic!("no expected result for {:?}", path),
+    }
+}
+
+#[test]
+fn corpus() {
+    let paths = inputs();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let data = std::fs::read(&path).unwrap();
+        let expected = expected(&path);
+        for _ in 0..2 {
+            let ours = explode::explode(&data).map_err(|e| e.to_string());
+            assert_eq!(ours, expected, "{:?}", path);
+        }
+    }
+}
+
+#[test]
+fn corpus_reader() {
+    for path in inputs() {
+        let data = std::fs::read(&path).unwrap();
+        let mut r = explode::ExplodeReader::new(&data[..]);
+        let mut ours = vec![];
+        let result = r.read_to_end(&mut ours);
+        match expected(&path) {
+            Ok(out) => assert_eq!(ours, out, "{:?}", path),
+            Err(_) => assert!(result.is_err(), "{:?}", path),
+        }
+    }
+}
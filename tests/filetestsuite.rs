@@ -0,0 +1,149 @@
+// run the fileTestSuite vectors for implode, if they are present
+//
+// drop the vectors into tests/filetestsuite, or point
+// EXPLODE_FILETESTSUITE at them. every directory there with a
+// meta.ftsmeta file is one suite. its metadata names two extensions:
+// each file ending in processed_ext is compressed, and explodes to the
+// file with the same name ending in raw_ext instead
+//
+// the metadata is a list of records, each a key and then a value,
+// each of those a length byte and then that many bytes of UTF-8. keys
+// other than raw_ext and processed_ext are ignored
+//
+// this only runs with the filetestsuite feature, and without the
+// vectors it does nothing
+
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Meta {
+    raw_ext: String,
+    processed_ext: String,
+}
+
+fn parse_meta(data: &[u8]) -> Result<Meta, String> {
+    fn field<'a>(data: &mut &'a [u8]) -> Result<&'a str, String> {
+        let (len, rest) = data.split_first().ok_or("truncated record")?;
+        if rest.len() < *len as usize {
+            return Err("truncated record".to_owned());
+        }
+        let (value, rest) = rest.split_at(*len as usize);
+        *data = rest;
+        std::str::from_utf8(value).map_err(|e| e.to_string())
+    }
+
+    let mut data = data;
+    let (mut raw_ext, mut processed_ext) = (None, None);
+    while !data.is_empty() {
+        let key = field(&mut data)?;
+        let value = field(&mut data)?.to_owned();
+        match key {
+            "raw_ext" => raw_ext = Some(value),
+            "processed_ext" => processed_ext = Some(value),
+            _ => (),
+        }
+    }
+    Ok(Meta {
+        raw_ext: raw_ext.ok_or("no raw_ext")?,
+        processed_ext: processed_ext.ok_or("no processed_ext")?,
+    })
+}
+
+fn root() -> PathBuf {
+    match std::env::var_os("EXPLODE_FILETESTSUITE") {
+        Some(dir) => dir.into(),
+        None => {
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/filetestsuite")
+        }
+    }
+}
+
+// the suite directories under root, including root itself
+fn suites(root: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(root)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.push(root.to_owned());
+    dirs.sort();
+    dirs.retain(|d| d.join("meta.ftsmeta").is_file());
+    dirs
+}
+
+// the (compressed, raw) files in one suite
+fn pairs(dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let data = std::fs::read(dir.join("meta.ftsmeta")).unwrap();
+    let meta = parse_meta(&data)
+        .unwrap_or_else(|e| panic!("bad metadata in {:?}: {}", dir, e));
+    let suffix = format!(".{}", meta.processed_ext);
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    paths.sort();
+    let mut pairs = vec![];
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let stem = match name.strip_suffix(&suffix) {
+            Some(stem) if path.is_file() => stem,
+            _ => continue,
+        };
+        let raw = match meta.raw_ext.as_str() {
+            "" => dir.join(stem),
+            ext => dir.join(format!("{}.{}", stem, ext)),
+        };
+        assert!(raw.is_file(), "no raw file for {:?}", path);
+        pairs.push((path, raw));
+    }
+    pairs
+}
+
+#[test]
+fn filetestsuite() {
+    let root = root();
+    if !root.is_dir() {
+        eprintln!("skipping, no test vectors in {:?}", root);
+        return;
+    }
+
+    let suites = suites(&root);
+    assert!(!suites.is_empty(), "no meta.ftsmeta in {:?}", root);
+    for dir in suites {
+        let pairs = pairs(&dir);
+        assert!(!pairs.is_empty(), "no test files in {:?}", dir);
+        for (processed, raw) in pairs {
+            let processed = std::fs::read(&processed).unwrap();
+            let raw = std::fs::read(raw).unwrap();
+            let ours = explode::explode(&processed)
+                .unwrap_or_else(|e| panic!("{:?}: {}", dir, e));
+            assert_eq!(ours, raw, "{:?}", dir);
+        }
+    }
+}
+
+#[test]
+fn filetestsuite_meta() {
+    let mut data = vec![];
+    for (key, value) in [
+        ("processed_ext", "imploded"),
+        ("comment", "ignored"),
+        ("raw_ext", ""),
+    ]
+    .iter()
+    {
+        data.push(key.len() as u8);
+        data.extend_from_slice(key.as_bytes());
+        data.push(value.len() as u8);
+        data.extend_from_slice(value.as_bytes());
+    }
+    let meta = Meta {
+        raw_ext: "".to_owned(),
+        processed_ext: "imploded".to_owned(),
+    };
+    assert_eq!(parse_meta(&data), Ok(meta));
+
+    assert!(parse_meta(&data[..data.len() - 1]).is_err());
+    assert!(parse_meta(&[]).is_err());
+}
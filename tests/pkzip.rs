@@ -0,0 +1,49 @@
+// decode the imploded member of every ZIP archive in tests/pkzip, and
+// compare against the expected output
+
+use std::convert::TryInto;
+use std::ffi::OsStr;
+use std::path::Path;
+
+fn u16_at(data: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes(data[i..i + 2].try_into().unwrap())
+}
+
+fn u32_at(data: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes(data[i..i + 4].try_into().unwrap())
+}
+
+// read the first member from a ZIP archive's local file header
+// returns (flags, compressed data, uncompressed size)
+fn first_member(zip: &[u8]) -> (u16, &[u8], usize) {
+    assert_eq!(u32_at(zip, 0), 0x04034b50);
+    assert_eq!(u16_at(zip, 8), 6, "not imploded");
+    let flags = u16_at(zip, 6);
+    let compressed = u32_at(zip, 18) as usize;
+    let uncompressed = u32_at(zip, 22) as usize;
+    let start = 30 + u16_at(zip, 26) as usize + u16_at(zip, 28) as usize;
+    (flags, &zip[start..start + compressed], uncompressed)
+}
+
+#[test]
+fn pkzip() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/pkzip");
+    let expected = std::fs::read(root.join("undhr.txt")).unwrap();
+    let mut paths: Vec<_> = std::fs::read_dir(&root)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension() == Some(OsStr::new("zip")))
+        .collect();
+    paths.sort();
+    assert_eq!(paths.len(), 4);
+
+    for path in paths {
+        let zip = std::fs::read(&path).unwrap();
+        let (flags, data, size) = first_member(&zip);
+        let ours = explode::pkzip::explode(data, size, flags).unwrap();
+        assert!(ours == expected, "mismatch in {:?}", path);
+
+        // these are not DCL implode streams
+        assert!(explode::explode(data).is_err());
+    }
+}
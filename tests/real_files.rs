@@ -0,0 +1,63 @@
+// decode every compressed file in the example directories, and compare
+// against the decompressed file next to it
+//
+// compressed files end in .z, and the decompressed file has the same
+// name with any other extension
+//
+// the files in tests/data were made by PKLib, not by PKWARE's own
+// tools. see tests/data/README.md
+
+use std::ffi::OsStr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+static DIRS: &[&str] = &["src/examples", "tests/data"];
+
+fn pairs() -> Vec<(PathBuf, PathBuf)> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut pairs = vec![];
+    for dir in DIRS.iter() {
+        let entries = match std::fs::read_dir(root.join(dir)) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let mut paths: Vec<PathBuf> =
+            entries.map(|e| e.unwrap().path()).collect();
+        paths.sort();
+
+        for path in paths.iter() {
+            if path.extension() != Some(OsStr::new("z")) {
+                continue;
+            }
+            let decoded = paths
+                .iter()
+                .find(|p| *p != path && p.file_stem() == path.file_stem())
+                .unwrap_or_else(|| panic!("no output for {:?}", path));
+            pairs.push((path.clone(), decoded.clone()));
+        }
+    }
+    pairs
+}
+
+#[test]
+fn real_files() {
+    let pairs = pairs();
+    assert!(!pairs.is_empty());
+    for (encoded, decoded) in pairs {
+        let encoded = std::fs::read(encoded).unwrap();
+        let decoded = std::fs::read(decoded).unwrap();
+        assert_eq!(explode::explode(&encoded).unwrap(), decoded);
+    }
+}
+
+#[test]
+fn real_files_reader() {
+    for (encoded, decoded) in pairs() {
+        let file = std::fs::File::open(encoded).unwrap();
+        // no BufReader needed, a buffered ExplodeReader reads in blocks
+        let mut r = explode::ExplodeReader::buffered(file);
+        let mut ours = vec![];
+        r.read_to_end(&mut ours).unwrap();
+        assert_eq!(ours, std::fs::read(decoded).unwrap());
+    }
+}
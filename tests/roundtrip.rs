@@ -0,0 +1,60 @@
+// property tests: anything imploded explodes back to the same bytes,
+// and no input at all makes explode panic
+
+use explode::{DictSize, Error, LiteralMode};
+use proptest::prelude::*;
+
+fn lit_mode() -> impl Strategy<Value = LiteralMode> {
+    prop_oneof![Just(LiteralMode::Uncoded), Just(LiteralMode::Coded)]
+}
+
+fn dict_size() -> impl Strategy<Value = DictSize> {
+    prop_oneof![
+        Just(DictSize::Size1K),
+        Just(DictSize::Size2K),
+        Just(DictSize::Size4K),
+        Just(DictSize::Auto),
+    ]
+}
+
+// mostly a small alphabet, so there are plenty of matches to copy
+fn data() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        prop::collection::vec(any::<u8>(), 0..2000),
+        prop::collection::vec(0..4u8, 0..5000),
+    ]
+}
+
+// bytes with a header that is usually valid, and anything after it
+fn compressed() -> impl Strategy<Value = Vec<u8>> {
+    (0..3u8, 3..8u8, prop::collection::vec(any::<u8>(), 0..500)).prop_map(
+        |(lit, dict, rest)| {
+            let mut data = vec![lit, dict];
+            data.extend(rest);
+            data
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn roundtrip(data in data(), lit in lit_mode(), dict in dict_size()) {
+        let encoded = explode::implode(&data, lit, dict).unwrap();
+        prop_assert_eq!(explode::explode(&encoded).unwrap(), data);
+
+        // and cut short, it is always incomplete
+        let cut = &encoded[..encoded.len() - 1];
+        prop_assert_eq!(explode::explode(cut), Err(Error::IncompleteInput));
+    }
+
+    #[test]
+    fn arbitrary(data in compressed()) {
+        // the result doesn't matter, as long as there is one
+        let _ = explode::explode(&data);
+    }
+
+    #[test]
+    fn arbitrary_any(data in prop::collection::vec(any::<u8>(), 0..100)) {
+        let _ = explode::explode(&data);
+    }
+}